@@ -1,44 +1,369 @@
-use iced::{executor, Application, Command, Element, Subscription};
+use iced::event::{self, Event};
+use iced::{mouse, window};
+use iced::{executor, Application, Command, Element, Point, Subscription};
 
+use super::input;
+use super::system_theme;
+use super::theme::{AppTheme, Palette};
 use super::ui;
-use super::clicking::ClickingEngine;
-use super::hotkeys::{self, HotkeyEvent};
-use super::settings::Settings;
-use super::theme::AppTheme;
-use std::thread;
-use std::time::{Duration, Instant};
-use dark_light;
+use super_clicker::benchmark::{BenchmarkRun, Report};
+use super_clicker::bundle::{self, Bundle, ImportPlan};
+use super_clicker::clicking::{self, ClickerEngine, ClickingEngine};
+use super_clicker::clock::{Clock, SystemClock};
+use super_clicker::history::{self, ExportFormat, Session, StopReason};
+use super_clicker::hotkeys::{self, FailSafeTrigger, HotkeyEvent};
+use super_clicker::keys;
+use super_clicker::logging;
+use super_clicker::macros::Macro;
+use super_clicker::scheduler::{self, LocalNow, ScheduleEvent, Scheduler};
+use super_clicker::scripting::{self, ScriptRun};
+use super_clicker::settings::{
+    Action, ActionKind, AreaShape, ClickArea, ClickerConfig, Hotkey, IntervalStepMode, LogLevel, Motion, MotionKind,
+    PatternStep, Schedule, ScheduleKind, ScheduleRepeat, Settings, Spread, SCROLL_NOTCHES, StepKind, ThemePreference,
+};
+use super_clicker::sound;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
 
 pub struct SuperClicker {
     status: String,
-    interval_input: String,
-    mouse_button_selected: String,
+    clickers: Vec<Clicker>,
+    active_clicker: usize, // Target of wheel/arrow interval adjustment
     enable_dynamic_adjustment: bool,
-    is_running: bool,
-    clicking_engine: ClickingEngine,
+    interval_step_mode: IntervalStepMode,
     settings: Settings,
-    last_toggle: Instant,
     // Local state for foreground hotkeys
     ctrl_pressed: bool,
     alt_pressed: bool,
     last_scroll_time: Option<Instant>, // Changed to Option
+    theme_preference: ThemePreference,
     current_theme: AppTheme,
+    palette_modified: Option<SystemTime>, // For hot-reloading the custom palette
+    settings_modified: Option<SystemTime>, // For picking up outside edits to settings.json
+    /// Why the last outside edit of settings.json was rejected. Saving waits
+    /// until the file is fixed, so the edit isn't overwritten.
+    settings_error: Option<String>,
+    // Compact always-on-top strip
+    mini_mode: bool,
+    /// When the strip last moved, while its position is unsaved.
+    mini_moved_at: Option<Instant>,
+    main_window_position: Option<(i32, i32)>,
+    start_delay_input: String,
+    scheduler: Scheduler,
+    schedule_form: ScheduleForm,
+    scripts: ScriptPanel,
+    /// Tail of the log file while the log viewer is open.
+    log_lines: Option<Vec<String>>,
+    /// Recorded sessions, oldest first.
+    history: Vec<Session>,
+    benchmark: BenchmarkPanel,
+    bundle: BundlePanel,
+    /// Clicker and interval waiting for the low-interval confirmation.
+    pending_low_interval: Option<(usize, u64)>,
+    environment: Environment,
+}
+
+/// Outside effects of the app, replaceable in tests.
+pub struct Environment {
+    pub clock: Arc<dyn Clock>,
+    /// Creates the engine behind each clicker.
+    pub new_engine: Box<dyn Fn() -> Box<dyn ClickerEngine>>,
+    /// Read and write files in the config directory (settings, scripts).
+    pub persist: bool,
+}
+
+impl Environment {
+    /// Real time, real input, settings saved to disk.
+    pub fn system() -> Self {
+        Environment {
+            clock: Arc::new(SystemClock),
+            new_engine: Box::new(|| Box::new(ClickingEngine::new())),
+            persist: true,
+        }
+    }
+}
+
+/// One clicker card: its saved configuration plus runtime and input state.
+pub struct Clicker {
+    pub config: ClickerConfig,
+    pub interval_input: String,
+    pub target_input: String,
+    pub scroll_input: String, // Notches of a scroll action
+    pub motion_input: String, // Jiggle pixels, path points or circle
+    pub speed_input: String,
+    pub glide_input: String,
+    /// Points clicked so far while capturing the click area.
+    pub capture: Option<Vec<(i32, i32)>>,
+    pub step_inputs: Vec<String>, // Wait ms, key name or notches of each pattern step
+    pub is_running: bool,
+    /// The current run was started by a schedule, which may also stop it.
+    by_schedule: bool,
+    pub countdown: Option<u32>, // Seconds left before clicking starts
+    pub click_count: u64,
+    engine: Box<dyn ClickerEngine>,
+    clock: Arc<dyn Clock>,
+    last_toggle: Instant,
+    /// Wall-clock and monotonic start of the current session.
+    started: Option<(SystemTime, Instant)>,
+}
+
+impl Clicker {
+    fn new(config: ClickerConfig, environment: &Environment) -> Self {
+        let now = environment.clock.now();
+        Clicker {
+            interval_input: config.interval_ms.to_string(),
+            target_input: config.target.map(|(x, y)| format!("{}, {}", x, y)).unwrap_or_default(),
+            scroll_input: scroll_notches(&config.action).to_string(),
+            motion_input: motion_input(&config.motion),
+            speed_input: motion_speed(&config.motion).map(|speed| speed.to_string()).unwrap_or_default(),
+            glide_input: config.glide_ms.to_string(),
+            capture: None,
+            step_inputs: config.pattern.iter().map(step_input).collect(),
+            config,
+            is_running: false,
+            by_schedule: false,
+            countdown: None,
+            click_count: 0,
+            engine: (environment.new_engine)(),
+            clock: Arc::clone(&environment.clock),
+            // Allow an immediate first toggle.
+            last_toggle: now.checked_sub(Duration::from_secs(1)).unwrap_or(now),
+            started: None,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_running || self.countdown.is_some()
+    }
+
+    fn start(&mut self) {
+        info!(clicker = %self.config.name, "clicker started");
+        self.countdown = None;
+        self.is_running = true;
+        self.by_schedule = false;
+        self.started = Some((SystemTime::now(), self.clock.now()));
+        self.engine.start(&self.config);
+    }
+
+    /// Stops clicking or cancels the countdown. Returns the session that
+    /// ended, if the clicker was clicking.
+    fn stop(&mut self, reason: StopReason) -> Option<Session> {
+        if self.is_active() {
+            info!(clicker = %self.config.name, clicks = self.engine.click_count(), %reason, "clicker stopped");
+        }
+        self.countdown = None;
+        self.is_running = false;
+        self.by_schedule = false;
+        self.engine.stop();
+        self.click_count = self.engine.click_count();
+
+        let (started, since) = self.started.take()?;
+        let action = match self.config.pattern.len() {
+            0 => ui::action_label(&self.config.action),
+            steps => format!("Pattern ({} steps)", steps),
+        };
+        Some(Session {
+            clicker: self.config.name.clone(),
+            action,
+            interval_ms: self.config.interval_ms,
+            started: started.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            duration_ms: self.clock.now().duration_since(since).as_millis() as u64,
+            clicks: self.click_count,
+            stop_reason: reason,
+        })
+    }
+}
+
+/// Inputs of the "add schedule" row.
+#[derive(Debug, Clone)]
+pub struct ScheduleForm {
+    /// `ClickerConfig::id` of the picked clicker.
+    pub clicker: Option<u32>,
+    pub repeat: ScheduleRepeat,
+    /// "HH:MM" for one-off and daily runs, minutes for repeating ones.
+    pub when_input: String,
+    pub run_secs_input: String,
+}
+
+impl Default for ScheduleForm {
+    fn default() -> Self {
+        ScheduleForm {
+            clicker: None,
+            repeat: ScheduleRepeat::default(),
+            when_input: String::from("09:00"),
+            run_secs_input: String::from("0"),
+        }
+    }
+}
+
+impl ScheduleForm {
+    fn to_schedule(&self) -> Option<Schedule> {
+        let kind = match self.repeat {
+            ScheduleRepeat::Once => {
+                let (hour, minute) = scheduler::parse_time_of_day(&self.when_input)?;
+                ScheduleKind::Once { hour, minute }
+            }
+            ScheduleRepeat::Daily => {
+                let (hour, minute) = scheduler::parse_time_of_day(&self.when_input)?;
+                ScheduleKind::Daily { hour, minute }
+            }
+            ScheduleRepeat::Every => ScheduleKind::Every {
+                minutes: self.when_input.trim().parse().ok().filter(|minutes| *minutes > 0)?,
+            },
+        };
+        Some(Schedule {
+            clicker_id: self.clicker?,
+            clicker: None,
+            kind,
+            run_secs: self.run_secs_input.trim().parse().ok()?,
+            enabled: true,
+        })
+    }
+}
+
+/// A clicker in the schedule picker. Names may repeat; ids don't.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickerChoice {
+    pub id: u32,
+    pub name: String,
+}
+
+impl fmt::Display for ClickerChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Script picker and the script currently running, if any.
+pub struct ScriptPanel {
+    pub names: Vec<String>,
+    pub selected: Option<String>,
+    pub run: Option<ScriptRun>,
+}
+
+/// What to put in an exported bundle, and the import being previewed.
+#[derive(Default)]
+pub struct BundlePanel {
+    pub name: String,
+    /// Indices of the clickers to export.
+    pub clickers: BTreeSet<usize>,
+    pub macro_names: Vec<String>,
+    pub macros: BTreeSet<String>,
+    pub hotkeys: bool,
+    pub bundle_names: Vec<String>,
+    pub selected: Option<String>,
+    /// Replace local items with the same name instead of renaming.
+    pub replace: bool,
+    pub preview: Option<(Bundle, ImportPlan)>,
+}
+
+/// The running timing benchmark and the last report.
+#[derive(Default)]
+pub struct BenchmarkPanel {
+    pub run: Option<BenchmarkRun>,
+    pub report: Option<Report>,
+}
+
+/// Edits to a clicker's toggle hotkey.
+#[derive(Debug, Clone)]
+pub enum HotkeyField {
+    Ctrl(bool),
+    Alt(bool),
+    Shift(bool),
+    Key(String),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    IntervalInputChanged(String),
-    MouseButtonSelected(String),
+    AddClicker,
+    RemoveClicker(usize),
+    ClickerNameChanged(usize, String),
+    ClickerActionSelected(usize, ActionKind),
+    ClickerKeyChanged(usize, String),
+    ClickerScrollChanged(usize, String),
+    ClickerMotionSelected(usize, MotionKind),
+    ClickerMotionChanged(usize, String),
+    ClickerMotionSpeedChanged(usize, String),
+    ClickerGlideChanged(usize, String),
+    CaptureArea(usize), // Starts or cancels capturing the click area
+    PointCaptured(i32, i32),
+    ClearArea(usize),
+    AreaShapeSelected(usize, AreaShape),
+    AreaSpreadSelected(usize, Spread),
+    ClickerIntervalChanged(usize, String),
+    ClickerIntervalSubmitted(usize),
+    ClickerTargetChanged(usize, String),
+    ClickerHotkeyChanged(usize, HotkeyField),
+    PatternStepAdded(usize),
+    PatternStepRemoved(usize, usize),
+    PatternStepKindSelected(usize, usize, StepKind),
+    PatternStepValueChanged(usize, usize, String),
+    PatternImported(usize),
+    PatternExported(usize),
+    StartClicker(usize),
+    StopClicker(usize),
+    StopAll,
     DynamicAdjustmentToggled(bool),
-    Start,
-    Stop,
-    ToggleFromHotkey,
-    IntervalChange(i32), // From rdev
+    IntervalStepModeSelected(IntervalStepMode),
+    StartDelayInputChanged(String),
+    StartDelayTickToggled(bool),
+    CountdownTick,
+    ScheduleClickerSelected(ClickerChoice),
+    ScheduleRepeatSelected(ScheduleRepeat),
+    ScheduleWhenChanged(String),
+    ScheduleRunSecsChanged(String),
+    AddSchedule,
+    RemoveSchedule(usize),
+    ScheduleToggled(usize, bool),
+    SchedulerTick,
+    ScriptSelected(String),
+    RefreshScripts,
+    RunScript,
+    StopScript,
+    LogLevelSelected(LogLevel),
+    OpenLog,
+    ToggleLogViewer,
+    RefreshLog,
+    ExportHistory(ExportFormat),
+    ConfirmLowInterval,
+    CancelLowInterval,
+    RunBenchmark,
+    ApplyCalibration,
+    ResetCalibration,
+    BundleNameChanged(String),
+    BundleClickerToggled(usize, bool),
+    BundleMacroToggled(String, bool),
+    BundleHotkeysToggled(bool),
+    ExportBundle,
+    RefreshBundles, // Re-reads the macro and bundle folders
+    BundleSelected(String),
+    BundleReplaceToggled(bool),
+    PreviewBundle,
+    ImportBundle,
+    CancelImport,
+    ToggleFromHotkey(usize),
+    IntervalChange(i32), // From rdev (wheel or arrow keys)
     LocalScroll(f32),    // From iced
     ModifiersChanged(bool, bool),
+    FailSafe(FailSafeTrigger),
+    AutoStop(StopReason), // Idle or locked, from the auto-stop watcher
+    AutoStopIdleChanged(String),
+    AutoStopOnLockToggled(bool),
     NoOp,
-    CheckTheme,
-    ThemeChanged(AppTheme),
+    ThemePreferenceSelected(ThemePreference),
+    ThemeChanged(AppTheme), // From the system theme watcher
+    CheckPalette,
+    CheckSettings,
+    ToggleMiniMode,
+    DragWindow,
+    DragEnded, // Left button released after dragging the mini strip
+    WindowMoved(i32, i32),
+    CheckMiniPosition, // Saves the strip position once it stops moving
+    CloseRequested,
+    Tick, // Refreshes click counts while running
 }
 
 impl Application for SuperClicker {
@@ -48,32 +373,18 @@ impl Application for SuperClicker {
     type Flags = ();
 
     fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
-        let settings = Settings::load().unwrap_or_default();
-        
-        let mode = dark_light::detect();
-        let theme = match mode {
-            dark_light::Mode::Dark => AppTheme::Dark,
-            dark_light::Mode::Light => AppTheme::Light,
-            _ => AppTheme::Dark,
-        };
-
-        (
-            SuperClicker {
-                status: String::from("Stopped"),
-                interval_input: settings.interval_ms.to_string(),
-                mouse_button_selected: settings.mouse_button.clone(),
-                enable_dynamic_adjustment: settings.enable_dynamic_adjustment,
-                is_running: false,
-                clicking_engine: ClickingEngine::new(),
-                settings,
-                last_toggle: Instant::now(),
-                ctrl_pressed: false,
-                alt_pressed: false,
-                last_scroll_time: None, // Initialize as None
-                current_theme: theme,
-            },
-            Command::none(),
-        )
+        match Settings::reload() {
+            Ok(settings) => (SuperClicker::with_environment(settings, Environment::system()), Command::none()),
+            Err(error) => {
+                warn!(%error, "could not load settings");
+                // Run on defaults, but leave the file alone until it is fixed.
+                // The file's current time is recorded, so only a new edit retries.
+                let mut app = SuperClicker::with_environment(Settings::default(), Environment::system());
+                app.status = format!("settings.json not loaded: {}", error);
+                app.settings_error = Some(error.to_string());
+                (app, Command::none())
+            }
+        }
     }
     
     fn title(&self) -> String {
@@ -89,56 +400,543 @@ impl Application for SuperClicker {
         message: Self::Message,
     ) -> Command<Self::Message> {
         match message {
-            Message::IntervalInputChanged(value) => {
-                self.interval_input = value.clone();
-                if let Ok(ms) = value.parse::<u64>() {
-                    self.settings.interval_ms = ms;
-                    let _ = self.settings.save();
-                    self.clicking_engine.update_interval(ms);
+            Message::AddClicker => {
+                let config = ClickerConfig {
+                    id: ClickerConfig::new_id(|id| self.clickers.iter().any(|clicker| clicker.config.id == id)),
+                    name: free_clicker_name(&self.clickers),
+                    ..ClickerConfig::default()
+                };
+                self.clickers.push(Clicker::new(config, &self.environment));
+                self.save_settings();
+            }
+            Message::RemoveClicker(index) => {
+                // There is always at least one clicker.
+                if index < self.clickers.len() && self.clickers.len() > 1 {
+                    self.stop_clicker(index, StopReason::Removed);
+                    self.clickers.remove(index);
+                    // Indices after it shift down.
+                    self.bundle.clickers.clear();
+                    self.active_clicker = self.active_clicker.min(self.clickers.len() - 1);
+                    self.save_settings();
                 }
             }
-            Message::MouseButtonSelected(value) => {
-                self.mouse_button_selected = value.clone();
-                self.settings.mouse_button = value;
-                let _ = self.settings.save();
+            Message::ClickerNameChanged(index, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.config.name = value;
+                    self.save_settings();
+                }
+            }
+            Message::ClickerActionSelected(index, kind) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.config.action = clicker.config.action.with_kind(kind);
+                    clicker.scroll_input = scroll_notches(&clicker.config.action).to_string();
+                    self.save_settings();
+                }
+            }
+            Message::ClickerKeyChanged(index, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.config.action = Action::Press(canonical_key_name(value));
+                    self.save_settings();
+                }
+            }
+            Message::ClickerScrollChanged(index, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.scroll_input = value.clone();
+                    if let Action::Scroll(_, notches) = &mut clicker.config.action
+                        && let Ok(value) = value.parse::<u32>()
+                        && SCROLL_NOTCHES.contains(&value)
+                    {
+                        *notches = value;
+                        self.save_settings();
+                    }
+                }
+            }
+            Message::ClickerMotionSelected(index, kind) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.config.motion = clicker.config.motion.with_kind(kind);
+                    clicker.motion_input = motion_input(&clicker.config.motion);
+                    clicker.speed_input = motion_speed(&clicker.config.motion)
+                        .map(|speed| speed.to_string())
+                        .unwrap_or_default();
+                    self.save_settings();
+                }
+            }
+            Message::ClickerMotionChanged(index, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.motion_input = value.clone();
+                    if apply_motion_input(&mut clicker.config.motion, &value) {
+                        self.save_settings();
+                    }
+                }
+            }
+            Message::ClickerMotionSpeedChanged(index, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.speed_input = value.clone();
+                    if let Motion::Path { speed, .. } | Motion::Circle { speed, .. } = &mut clicker.config.motion
+                        && let Ok(value) = value.parse::<u32>()
+                        && value > 0
+                    {
+                        *speed = value;
+                        self.save_settings();
+                    }
+                }
+            }
+            Message::ClickerGlideChanged(index, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.glide_input = value.clone();
+                    if let Ok(ms) = value.parse::<u64>() {
+                        clicker.config.glide_ms = ms;
+                        self.save_settings();
+                    }
+                }
+            }
+            Message::CaptureArea(index) => {
+                let cancel = self.clickers.get(index).is_some_and(|clicker| clicker.capture.is_some());
+                for clicker in &mut self.clickers {
+                    clicker.capture = None;
+                }
+                if cancel {
+                    hotkeys::set_capturing(false);
+                    self.status = String::from("Area capture cancelled");
+                } else if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.capture = Some(Vec::new());
+                    hotkeys::set_capturing(true);
+                    self.status = match clicker.config.area.map(|area| area.shape) {
+                        Some(AreaShape::Circle) => String::from("Click the center of the area"),
+                        _ => String::from("Click one corner of the area"),
+                    };
+                }
+            }
+            Message::PointCaptured(x, y) => {
+                let Some(clicker) = self.clickers.iter_mut().find(|clicker| clicker.capture.is_some()) else {
+                    // The clicker was removed mid-capture.
+                    hotkeys::set_capturing(false);
+                    return Command::none();
+                };
+                let points = clicker.capture.get_or_insert_with(Vec::new);
+                points.push((x, y));
+                if let [from, to] = points[..] {
+                    let previous = clicker.config.area.unwrap_or_default();
+                    clicker.config.area = Some(ClickArea { from, to, ..previous });
+                    clicker.capture = None;
+                    hotkeys::set_capturing(false);
+                    self.status = format!("Click area set for {}", clicker.config.name);
+                    self.save_settings();
+                } else {
+                    self.status = match clicker.config.area.map(|area| area.shape) {
+                        Some(AreaShape::Circle) => String::from("Click the edge of the area"),
+                        _ => String::from("Click the opposite corner"),
+                    };
+                }
+            }
+            Message::ClearArea(index) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.config.area = None;
+                    self.save_settings();
+                }
+            }
+            Message::AreaShapeSelected(index, shape) => {
+                if let Some(clicker) = self.clickers.get_mut(index)
+                    && let Some(area) = &mut clicker.config.area
+                {
+                    *area = area.with_shape(shape);
+                    self.save_settings();
+                }
+            }
+            Message::AreaSpreadSelected(index, spread) => {
+                if let Some(clicker) = self.clickers.get_mut(index)
+                    && let Some(area) = &mut clicker.config.area
+                {
+                    area.spread = spread;
+                    self.save_settings();
+                }
+            }
+            Message::ClickerIntervalChanged(index, value) => {
+                let Some(clicker) = self.clickers.get_mut(index) else {
+                    return Command::none();
+                };
+                clicker.interval_input = value.clone();
+                // A low value waits for Enter or Start: "150" passes through "1" on the way.
+                match value.parse::<u64>() {
+                    Ok(ms) if ms > 0 && !self.needs_low_interval_confirmation(ms) => self.apply_interval(index, ms),
+                    _ => {}
+                }
+            }
+            Message::ClickerIntervalSubmitted(index) => {
+                self.ask_about_typed_interval(index);
+            }
+            Message::ClickerTargetChanged(index, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    clicker.target_input = value.clone();
+                    if value.trim().is_empty() {
+                        clicker.config.target = None;
+                        self.save_settings();
+                    } else if let Some(target) = parse_point(&value) {
+                        clicker.config.target = Some(target);
+                        self.save_settings();
+                    }
+                }
+            }
+            Message::ClickerHotkeyChanged(index, field) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    let hotkey = &mut clicker.config.hotkey;
+                    match field {
+                        HotkeyField::Ctrl(value) => hotkey.ctrl = value,
+                        HotkeyField::Alt(value) => hotkey.alt = value,
+                        HotkeyField::Shift(value) => hotkey.shift = value,
+                        HotkeyField::Key(value) => {
+                            hotkey.key = (!value.trim().is_empty()).then(|| canonical_key_name(value));
+                        }
+                    }
+                    self.save_settings();
+                }
+            }
+            Message::PatternStepAdded(index) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    // Start with the clicker's plain action so the pattern does something right away.
+                    let step = match clicker.config.pattern.last() {
+                        Some(PatternStep::Act(_)) => PatternStep::Wait(clicker.config.interval_ms),
+                        _ => PatternStep::Act(clicker.config.action.clone()),
+                    };
+                    clicker.step_inputs.push(step_input(&step));
+                    clicker.config.pattern.push(step);
+                    self.save_settings();
+                }
+            }
+            Message::PatternStepRemoved(index, step) => {
+                if let Some(clicker) = self.clickers.get_mut(index)
+                    && step < clicker.config.pattern.len()
+                {
+                    clicker.config.pattern.remove(step);
+                    clicker.step_inputs.remove(step);
+                    self.save_settings();
+                }
+            }
+            Message::PatternStepKindSelected(index, step, kind) => {
+                if let Some(clicker) = self.clickers.get_mut(index)
+                    && let Some(current) = clicker.config.pattern.get_mut(step)
+                {
+                    *current = current.with_kind(kind);
+                    clicker.step_inputs[step] = step_input(current);
+                    self.save_settings();
+                }
+            }
+            Message::PatternStepValueChanged(index, step, value) => {
+                if let Some(clicker) = self.clickers.get_mut(index)
+                    && let Some(current) = clicker.config.pattern.get_mut(step)
+                {
+                    clicker.step_inputs[step] = value.clone();
+                    match current {
+                        PatternStep::Wait(ms) => {
+                            if let Ok(value) = value.parse::<u64>() {
+                                *ms = value;
+                                self.save_settings();
+                            }
+                        }
+                        PatternStep::MoveTo(x, y) => {
+                            if let Some(point) = parse_point(&value) {
+                                (*x, *y) = point;
+                                self.save_settings();
+                            }
+                        }
+                        PatternStep::Act(Action::Press(key)) => {
+                            *key = canonical_key_name(value);
+                            self.save_settings();
+                        }
+                        PatternStep::Act(Action::Scroll(_, notches)) => {
+                            if let Ok(value) = value.parse::<u32>()
+                                && SCROLL_NOTCHES.contains(&value)
+                            {
+                                *notches = value;
+                                self.save_settings();
+                            }
+                        }
+                        PatternStep::Act(Action::Click(_)) => {}
+                    }
+                }
+            }
+            Message::PatternImported(index) => {
+                if let Some(clicker) = self.clickers.get_mut(index) {
+                    match Macro::load(&clicker.config.name) {
+                        Ok(text_macro) => {
+                            clicker.config.pattern = text_macro.to_steps();
+                            clicker.step_inputs = clicker.config.pattern.iter().map(step_input).collect();
+                            self.status = format!("Loaded pattern of {}", clicker.config.name);
+                            self.save_settings();
+                        }
+                        Err(error) => self.status = format!("Could not load macro: {}", error),
+                    }
+                }
+            }
+            Message::PatternExported(index) => {
+                if let Some(clicker) = self.clickers.get(index) {
+                    self.status = match Macro::from_steps(&clicker.config.pattern).save(&clicker.config.name) {
+                        Ok(path) => format!("Saved pattern to {}", path.display()),
+                        Err(error) => format!("Could not save macro: {}", error),
+                    };
+                }
+            }
+            Message::StartClicker(index) => {
+                if !self.ask_about_typed_interval(index) {
+                    self.request_start(index);
+                }
+            }
+            Message::StopClicker(index) => {
+                if index < self.clickers.len() {
+                    self.stop_clicker(index, StopReason::Manual);
+                    self.status = String::from("Stopped");
+                }
+            }
+            Message::StopAll => {
+                self.stop_all(StopReason::StopAll, String::from("Stopped"));
             }
             Message::DynamicAdjustmentToggled(value) => {
                 self.enable_dynamic_adjustment = value;
                 self.settings.enable_dynamic_adjustment = value;
-                let _ = self.settings.save();
-            }
-            Message::Start => {
-                if self.is_running {
-                    self.clicking_engine.stop();
-                    thread::sleep(Duration::from_millis(50));
-                }
-                self.is_running = true;
-                self.status = String::from("Running");
-                let interval_ms: u64 = self.interval_input.parse().unwrap_or(100);
-                self.clicking_engine.start(&self.mouse_button_selected, interval_ms);
-            }
-            Message::Stop => {
-                println!("[Stop] received");
-                self.is_running = false;
-                self.status = String::from("Stopped");
-                self.clicking_engine.stop();
-            }
-            Message::ToggleFromHotkey => {
-                let now = Instant::now();
-                if now.duration_since(self.last_toggle) < Duration::from_millis(300) {
+                self.save_settings();
+            }
+            Message::IntervalStepModeSelected(mode) => {
+                self.interval_step_mode = mode;
+                self.settings.interval_step_mode = mode;
+                self.save_settings();
+            }
+            Message::StartDelayInputChanged(value) => {
+                self.start_delay_input = value.clone();
+                if let Ok(secs) = value.parse::<u32>() {
+                    self.settings.start_delay_secs = secs;
+                    self.save_settings();
+                }
+            }
+            Message::StartDelayTickToggled(value) => {
+                self.settings.start_delay_tick = value;
+                self.save_settings();
+            }
+            Message::CountdownTick => {
+                let mut ticking = false;
+                for clicker in &mut self.clickers {
+                    match clicker.countdown {
+                        Some(remaining) if remaining <= 1 => clicker.start(),
+                        Some(remaining) => {
+                            clicker.countdown = Some(remaining - 1);
+                            ticking = true;
+                        }
+                        None => {}
+                    }
+                }
+                if ticking && self.settings.start_delay_tick {
+                    sound::tick();
+                }
+            }
+            Message::ScheduleClickerSelected(choice) => {
+                self.schedule_form.clicker = Some(choice.id);
+            }
+            Message::ScheduleRepeatSelected(repeat) => {
+                // Swap in a sensible example when switching between a time and a period.
+                if (repeat == ScheduleRepeat::Every) != (self.schedule_form.repeat == ScheduleRepeat::Every) {
+                    self.schedule_form.when_input = match repeat {
+                        ScheduleRepeat::Every => String::from("15"),
+                        _ => String::from("09:00"),
+                    };
+                }
+                self.schedule_form.repeat = repeat;
+            }
+            Message::ScheduleWhenChanged(value) => {
+                self.schedule_form.when_input = value;
+            }
+            Message::ScheduleRunSecsChanged(value) => {
+                self.schedule_form.run_secs_input = value;
+            }
+            Message::AddSchedule => {
+                if let Some(schedule) = self.schedule_form.to_schedule() {
+                    let index = self.settings.schedules.len();
+                    self.scheduler.refresh(index, &schedule, LocalNow::current());
+                    self.settings.schedules.push(schedule);
+                    self.save_settings();
+                }
+            }
+            Message::RemoveSchedule(index) => {
+                if index < self.settings.schedules.len() {
+                    self.settings.schedules.remove(index);
+                    self.scheduler.remove(index);
+                    self.save_settings();
+                }
+            }
+            Message::ScheduleToggled(index, enabled) => {
+                if let Some(schedule) = self.settings.schedules.get_mut(index) {
+                    schedule.enabled = enabled;
+                    self.scheduler.refresh(index, schedule, LocalNow::current());
+                    self.save_settings();
+                }
+            }
+            Message::SchedulerTick => {
+                let events = self.scheduler.poll(&self.settings.schedules, LocalNow::current());
+                for event in events {
+                    self.run_schedule_event(event);
+                }
+            }
+            Message::ScriptSelected(name) => {
+                self.scripts.selected = Some(name);
+            }
+            Message::RefreshScripts => {
+                self.scripts.names = if self.environment.persist { scripting::list() } else { Vec::new() };
+            }
+            Message::RunScript => {
+                if self.scripts.run.is_some() {
+                    return Command::none();
+                }
+                let Some(name) = self.scripts.selected.clone() else {
+                    return Command::none();
+                };
+                match scripting::load(&name) {
+                    Ok(source) => {
+                        let max_runtime = Duration::from_secs(self.settings.scripts.max_runtime_secs);
+                        info!(script = %name, "script started");
+                        self.scripts.run = Some(ScriptRun::start(name, source, max_runtime));
+                    }
+                    Err(error) => {
+                        warn!(script = %name, %error, "could not load script");
+                        self.status = format!("Could not load script: {}", error);
+                    }
+                }
+            }
+            Message::StopScript => {
+                if let Some(run) = &self.scripts.run {
+                    run.stop();
+                }
+            }
+            Message::LogLevelSelected(level) => {
+                logging::set_level(level);
+                self.settings.log_level = level;
+                self.save_settings();
+            }
+            Message::OpenLog => {
+                if let Err(error) = logging::open_latest() {
+                    self.status = format!("Could not open log: {}", error);
+                }
+            }
+            Message::ToggleLogViewer => {
+                self.log_lines = match self.log_lines {
+                    Some(_) => None,
+                    None => Some(logging::recent_lines(LOG_VIEWER_LINES)),
+                };
+            }
+            Message::RefreshLog => {
+                if self.log_lines.is_some() {
+                    self.log_lines = Some(logging::recent_lines(LOG_VIEWER_LINES));
+                }
+            }
+            Message::ExportHistory(format) => {
+                self.status = match history::export(&self.history, format) {
+                    Ok(path) => format!("Exported history to {}", path.display()),
+                    Err(error) => format!("Could not export history: {}", error),
+                };
+            }
+            Message::ConfirmLowInterval => {
+                if let Some((index, ms)) = self.pending_low_interval.take() {
+                    self.settings.rate_limit.low_interval_confirmed = true;
+                    self.apply_interval(index, ms);
+                }
+            }
+            Message::CancelLowInterval => {
+                if let Some((index, _)) = self.pending_low_interval.take()
+                    && let Some(clicker) = self.clickers.get_mut(index)
+                {
+                    clicker.interval_input = clicker.config.interval_ms.to_string();
+                }
+            }
+            Message::RunBenchmark => {
+                if self.benchmark.run.is_none() {
+                    self.benchmark.run = Some(BenchmarkRun::start());
+                }
+            }
+            Message::ApplyCalibration => {
+                if let Some(report) = &self.benchmark.report {
+                    self.set_timing_offset(report.suggested_offset_us());
+                }
+            }
+            Message::ResetCalibration => {
+                self.set_timing_offset(0);
+            }
+            Message::BundleNameChanged(value) => {
+                self.bundle.name = value;
+            }
+            Message::BundleClickerToggled(index, selected) => {
+                if selected {
+                    self.bundle.clickers.insert(index);
+                } else {
+                    self.bundle.clickers.remove(&index);
+                }
+            }
+            Message::BundleMacroToggled(name, selected) => {
+                if selected {
+                    self.bundle.macros.insert(name);
+                } else {
+                    self.bundle.macros.remove(&name);
+                }
+            }
+            Message::BundleHotkeysToggled(hotkeys) => {
+                self.bundle.hotkeys = hotkeys;
+            }
+            Message::ExportBundle => {
+                let name = self.bundle.name.trim();
+                let clickers: Vec<usize> = self.bundle.clickers.iter().copied().collect();
+                let macros: Vec<String> = self.bundle.macros.iter().cloned().collect();
+                self.status = match Bundle::collect(&self.settings, &clickers, &macros, self.bundle.hotkeys)
+                    .and_then(|bundle| bundle.save(if name.is_empty() { "bundle" } else { name }))
+                {
+                    Ok(path) => format!("Saved bundle to {}", path.display()),
+                    Err(error) => format!("Could not export bundle: {}", error),
+                };
+                self.bundle.bundle_names = bundle::list();
+            }
+            Message::RefreshBundles => {
+                if self.environment.persist {
+                    self.bundle.macro_names = Macro::list();
+                    self.bundle.bundle_names = bundle::list();
+                }
+            }
+            Message::BundleSelected(name) => {
+                self.bundle.selected = Some(name);
+                self.bundle.preview = None;
+            }
+            Message::BundleReplaceToggled(replace) => {
+                self.bundle.replace = replace;
+                if let Some((bundle, _)) = self.bundle.preview.take() {
+                    self.preview_bundle(bundle);
+                }
+            }
+            Message::PreviewBundle => {
+                if let Some(name) = &self.bundle.selected {
+                    match Bundle::load(name) {
+                        Ok(bundle) => self.preview_bundle(bundle),
+                        Err(error) => self.status = format!("Could not read bundle: {}", error),
+                    }
+                }
+            }
+            Message::ImportBundle => {
+                if let Some((_, plan)) = self.bundle.preview.take() {
+                    self.import_bundle(&plan);
+                }
+            }
+            Message::CancelImport => {
+                self.bundle.preview = None;
+            }
+            Message::ToggleFromHotkey(index) => {
+                let Some(clicker) = self.clickers.get_mut(index) else {
+                    return Command::none();
+                };
+                let now = self.environment.clock.now();
+                if now.duration_since(clicker.last_toggle) < Duration::from_millis(300) {
                     return Command::none();
                 }
-                self.last_toggle = now;
+                clicker.last_toggle = now;
 
-                if self.is_running {
-                    self.is_running = false;
+                // The toggle hotkey also cancels a pending start.
+                if clicker.is_active() {
+                    self.stop_clicker(index, StopReason::Hotkey);
                     self.status = String::from("Stopped");
-                    self.clicking_engine.stop();
                 } else {
-                    self.is_running = true;
-                    self.status = String::from("Running");
-                    let interval_ms: u64 = self.interval_input.parse().unwrap_or(100);
-                    self.clicking_engine.start(&self.mouse_button_selected, interval_ms);
+                    self.request_start(index);
                 }
             }
             Message::IntervalChange(delta) => {
@@ -153,20 +951,128 @@ impl Application for SuperClicker {
                     self.adjust_interval(delta_f32 as i32);
                 }
             }
-            Message::NoOp => {}
-            Message::CheckTheme => {
-                let mode = dark_light::detect();
-                let new_theme = match mode {
-                    dark_light::Mode::Dark => AppTheme::Dark,
-                    dark_light::Mode::Light => AppTheme::Light,
-                    _ => AppTheme::Dark,
-                };
-                if new_theme != self.current_theme {
-                    return Command::perform(async move { new_theme }, Message::ThemeChanged);
+            Message::FailSafe(trigger) => {
+                // Stop every engine unconditionally, even ones our state thinks are idle.
+                if self.clickers.iter().any(Clicker::is_active) || self.scripts.run.is_some() {
+                    self.stop_all(StopReason::FailSafe, format!("Stopped ({})", trigger));
+                } else {
+                    self.clickers.iter_mut().for_each(|clicker| clicker.engine.stop());
+                }
+            }
+            Message::AutoStop(reason) => {
+                // Sent every second while idle or locked, so a run started
+                // meanwhile (e.g. by a schedule) stops at the next one.
+                if self.clickers.iter().any(Clicker::is_active) || self.scripts.run.is_some() {
+                    self.stop_all(reason, format!("Stopped ({})", reason));
+                }
+            }
+            Message::AutoStopIdleChanged(value) => {
+                // Minutes; empty turns the idle stop off.
+                let minutes = if value.trim().is_empty() { Some(0) } else { value.trim().parse::<u64>().ok() };
+                if let Some(minutes) = minutes {
+                    self.settings.auto_stop.idle_secs = minutes * 60;
+                    hotkeys::set_auto_stop(self.settings.auto_stop);
+                    self.save_settings();
                 }
             }
+            Message::AutoStopOnLockToggled(value) => {
+                self.settings.auto_stop.stop_on_lock = value;
+                hotkeys::set_auto_stop(self.settings.auto_stop);
+                self.save_settings();
+            }
+            Message::NoOp => {}
+            Message::ThemePreferenceSelected(preference) => {
+                self.theme_preference = preference;
+                self.current_theme = resolve_theme(preference);
+                self.palette_modified = Palette::modified();
+                self.settings.theme_preference = preference;
+                self.save_settings();
+            }
             Message::ThemeChanged(theme) => {
-                self.current_theme = theme;
+                // A late notification must not override a fixed user choice.
+                if self.theme_preference == ThemePreference::System {
+                    self.current_theme = theme;
+                }
+            }
+            Message::CheckPalette => {
+                let modified = Palette::modified();
+                if modified != self.palette_modified {
+                    self.palette_modified = modified;
+                    // Keep the current colors while the file is mid-edit or invalid.
+                    match Palette::load() {
+                        Ok(palette) => {
+                            self.current_theme = AppTheme::Custom(palette);
+                            warn_low_contrast(self.current_theme);
+                        }
+                        Err(error) => warn!(%error, "could not reload palette"),
+                    }
+                }
+            }
+            Message::CheckSettings => {
+                let modified = Settings::modified();
+                // A deleted file is ignored; the next save writes it again.
+                if modified.is_some() && modified != self.settings_modified {
+                    self.settings_modified = modified;
+                    match Settings::reload() {
+                        Ok(settings) => self.apply_settings(settings),
+                        Err(error) => {
+                            warn!(%error, "could not reload settings");
+                            self.status = format!("settings.json not loaded: {}", error);
+                            self.settings_error = Some(error.to_string());
+                        }
+                    }
+                }
+            }
+            Message::ToggleMiniMode => {
+                return self.toggle_mini_mode();
+            }
+            Message::DragWindow => {
+                if self.mini_mode {
+                    return window::drag(window::Id::MAIN);
+                }
+            }
+            Message::DragEnded => {
+                // Moves arrive many times a second while dragging; save once at the end.
+                self.save_mini_position();
+            }
+            Message::CheckMiniPosition => {
+                // The OS move loop often swallows the button release, so also
+                // save once the strip has been still for a moment.
+                if self
+                    .mini_moved_at
+                    .is_some_and(|moved| self.environment.clock.now().duration_since(moved) >= MINI_POSITION_SAVE_DELAY)
+                {
+                    self.save_mini_position();
+                }
+            }
+            Message::CloseRequested => {
+                self.save_mini_position();
+                return window::close(window::Id::MAIN);
+            }
+            Message::WindowMoved(x, y) => {
+                if self.mini_mode {
+                    self.settings.mini_mode_position = Some((x, y));
+                    self.mini_moved_at = Some(self.environment.clock.now());
+                } else {
+                    self.main_window_position = Some((x, y));
+                }
+            }
+            Message::Tick => {
+                for clicker in &mut self.clickers {
+                    clicker.click_count = clicker.engine.click_count();
+                }
+                if let Some(run) = &self.scripts.run
+                    && let Some(outcome) = run.outcome()
+                {
+                    info!(script = %run.name, %outcome, "script ended");
+                    self.status = format!("Script {} {}", run.name, outcome);
+                    self.scripts.run = None;
+                }
+                if let Some(report) = self.benchmark.run.as_ref().and_then(BenchmarkRun::report) {
+                    info!(suggested_offset_us = report.suggested_offset_us(), "benchmark finished");
+                    self.benchmark.report = Some(report);
+                    self.benchmark.run = None;
+                }
             }
         }
 
@@ -174,38 +1080,430 @@ impl Application for SuperClicker {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
+        let theme_watch = match self.theme_preference {
+            ThemePreference::System => system_theme::subscribe().map(Message::ThemeChanged),
+            ThemePreference::Custom => {
+                iced::time::every(Duration::from_secs(1)).map(|_| Message::CheckPalette)
+            }
+            _ => Subscription::none(),
+        };
+
         Subscription::batch(vec![
-            hotkeys::subscribe().map(|event| match event {
-                HotkeyEvent::Toggle => Message::ToggleFromHotkey,
+            input::subscribe().map(|event| match event {
+                HotkeyEvent::Toggle(index) => Message::ToggleFromHotkey(index),
+                HotkeyEvent::ToggleMiniMode => Message::ToggleMiniMode,
                 HotkeyEvent::IntervalChange(delta) => Message::IntervalChange(delta),
+                HotkeyEvent::FailSafe(trigger) => Message::FailSafe(trigger),
+                HotkeyEvent::StopScript => Message::StopScript,
+                HotkeyEvent::AutoStop(reason) => Message::AutoStop(reason),
+                HotkeyEvent::PointCaptured(x, y) => Message::PointCaptured(x, y),
                 _ => Message::NoOp,
             }),
-            hotkeys::subscribe_local().map(|event| match event {
-                HotkeyEvent::Toggle => Message::ToggleFromHotkey,
+            input::subscribe_local().map(|event| match event {
                 HotkeyEvent::ModifiersChanged(c, a) => Message::ModifiersChanged(c, a),
                 HotkeyEvent::LocalScroll(d) => Message::LocalScroll(d),
                 _ => Message::NoOp,
             }),
-            iced::time::every(Duration::from_secs(1)).map(|_| Message::CheckTheme),
+            theme_watch,
+            if self.environment.persist {
+                iced::time::every(Duration::from_secs(1)).map(|_| Message::CheckSettings)
+            } else {
+                Subscription::none()
+            },
+            event::listen_with(window_event),
+            if self.mini_moved_at.is_some() {
+                iced::time::every(Duration::from_millis(250)).map(|_| Message::CheckMiniPosition)
+            } else {
+                Subscription::none()
+            },
+            if self.clickers.iter().any(|clicker| clicker.is_running)
+                || self.scripts.run.is_some()
+                || self.benchmark.run.is_some()
+            {
+                iced::time::every(Duration::from_millis(250)).map(|_| Message::Tick)
+            } else {
+                Subscription::none()
+            },
+            if self.clickers.iter().any(|clicker| clicker.countdown.is_some()) {
+                iced::time::every(Duration::from_secs(1)).map(|_| Message::CountdownTick)
+            } else {
+                Subscription::none()
+            },
+            // Also refreshes the next-fire times shown in the schedule list.
+            if self.scheduler.is_idle() {
+                Subscription::none()
+            } else {
+                iced::time::every(Duration::from_secs(1)).map(|_| Message::SchedulerTick)
+            },
         ])
     }
 
     fn view(&self) -> Element<'_, Self::Message, AppTheme> {
+        let status = self.status_summary();
+        let any_active = self.clickers.iter().any(Clicker::is_active) || self.scripts.run.is_some();
+
+        if let Some((_, ms)) = self.pending_low_interval {
+            return ui::low_interval_dialog(ms, &self.settings.rate_limit);
+        }
+
+        if self.mini_mode {
+            let interval_input = self
+                .clickers
+                .get(self.active_clicker)
+                .map_or("", |clicker| clicker.interval_input.as_str());
+            let click_count = self.clickers.iter().map(|clicker| clicker.click_count).sum();
+            return ui::mini_view(&status, interval_input, click_count, any_active);
+        }
+
+        let now = LocalNow::current();
+        let next_fires: Vec<Option<String>> = (0..self.settings.schedules.len())
+            .map(|index| {
+                self.scheduler
+                    .next_fire(index)
+                    .map(|fire| scheduler::describe_fire_time(fire, now))
+            })
+            .collect();
+
+        let offset = now.utc_offset_secs();
+        let recent_sessions: Vec<String> = self
+            .history
+            .iter()
+            .rev()
+            .take(HISTORY_VIEW_SESSIONS)
+            .map(|session| describe_session(session, offset))
+            .collect();
+
         ui::view(
-            &self.status,
-            &self.interval_input,
-            self.mouse_button_selected.clone(),
-            self.enable_dynamic_adjustment,
-            self.is_running,
+            &status,
+            &self.clickers,
+            &self.settings,
+            &self.start_delay_input,
+            &self.schedule_form,
+            &next_fires,
+            &self.scripts,
+            self.log_lines.as_deref(),
+            &recent_sessions,
+            &self.benchmark,
+            &self.bundle,
+            self.settings_error.as_deref(),
         )
     }
 }
 
 impl SuperClicker {
+    /// Builds the app state from `settings`, with time, engines and file
+    /// access coming from `environment`.
+    pub fn with_environment(mut settings: Settings, environment: Environment) -> Self {
+        settings.migrate_legacy_clicker();
+        settings.assign_clicker_ids();
+        let theme = resolve_theme(settings.theme_preference);
+        let start_delay_input = settings.start_delay_secs.to_string();
+        apply_global_settings(&settings);
+        let scheduler = Scheduler::new(&settings.schedules, LocalNow::current());
+        let script_names = if environment.persist { scripting::list() } else { Vec::new() };
+        let history = if environment.persist {
+            history::load().unwrap_or_else(|error| {
+                warn!(%error, "could not load history");
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+
+        SuperClicker {
+            status: String::from("Stopped"),
+            clickers: settings
+                .clickers
+                .iter()
+                .map(|config| Clicker::new(config.clone(), &environment))
+                .collect(),
+            active_clicker: 0,
+            enable_dynamic_adjustment: settings.enable_dynamic_adjustment,
+            interval_step_mode: settings.interval_step_mode,
+            theme_preference: settings.theme_preference,
+            settings,
+            ctrl_pressed: false,
+            alt_pressed: false,
+            last_scroll_time: None, // Initialize as None
+            current_theme: theme,
+            palette_modified: Palette::modified(),
+            settings_modified: if environment.persist { Settings::modified() } else { None },
+            settings_error: None,
+            mini_mode: false,
+            mini_moved_at: None,
+            main_window_position: None,
+            start_delay_input,
+            scheduler,
+            schedule_form: ScheduleForm::default(),
+            scripts: ScriptPanel {
+                names: script_names,
+                selected: None,
+                run: None,
+            },
+            log_lines: None,
+            history,
+            benchmark: BenchmarkPanel::default(),
+            bundle: BundlePanel {
+                name: String::from("bundle"),
+                macro_names: if environment.persist { Macro::list() } else { Vec::new() },
+                hotkeys: true,
+                bundle_names: if environment.persist { bundle::list() } else { Vec::new() },
+                ..BundlePanel::default()
+            },
+            pending_low_interval: None,
+            environment,
+        }
+    }
+
+    /// Starts a clicker, after the configured countdown if there is one.
+    fn request_start(&mut self, index: usize) {
+        let delay = self.settings.start_delay_secs;
+        let Some(clicker) = self.clickers.get_mut(index) else {
+            return;
+        };
+
+        self.active_clicker = index;
+        if delay == 0 {
+            clicker.start();
+        } else {
+            clicker.countdown = Some(delay);
+            if self.settings.start_delay_tick {
+                sound::tick();
+            }
+        }
+    }
+
+    /// Starts or stops the clicker a schedule refers to. Scheduled starts skip
+    /// the countdown, and never interrupt or take over a clicker that is
+    /// already active.
+    fn run_schedule_event(&mut self, event: ScheduleEvent) {
+        let (ScheduleEvent::Start(index) | ScheduleEvent::Stop(index)) = event;
+        let Some(schedule) = self.settings.schedules.get(index) else {
+            return;
+        };
+        let Some(clicker_index) = self.clickers.iter().position(|clicker| clicker.config.id == schedule.clicker_id) else {
+            warn!(clicker_id = schedule.clicker_id, "schedule refers to unknown clicker");
+            return;
+        };
+        let once = matches!(schedule.kind, ScheduleKind::Once { .. });
+        let clicker = &mut self.clickers[clicker_index];
+
+        match event {
+            ScheduleEvent::Start(_) => {
+                if !clicker.is_active() {
+                    clicker.start();
+                    clicker.by_schedule = true;
+                    self.active_clicker = clicker_index;
+                }
+                if once {
+                    self.settings.schedules[index].enabled = false;
+                    self.save_settings();
+                }
+            }
+            ScheduleEvent::Stop(_) => {
+                // A run the user started by hand is theirs to stop.
+                if clicker.is_running && clicker.by_schedule {
+                    self.stop_clicker(clicker_index, StopReason::Schedule);
+                    self.status = String::from("Stopped (schedule)");
+                }
+            }
+        }
+    }
+
+    fn set_timing_offset(&mut self, offset_us: u64) {
+        clicking::set_timing_offset(Duration::from_micros(offset_us));
+        self.settings.timing_offset_us = offset_us;
+        self.save_settings();
+    }
+
+    fn stop_clicker(&mut self, index: usize, reason: StopReason) {
+        if let Some(session) = self.clickers.get_mut(index).and_then(|clicker| clicker.stop(reason)) {
+            self.record(session);
+        }
+    }
+
+    /// Adds a finished session to the history.
+    fn record(&mut self, session: Session) {
+        if self.environment.persist
+            && let Err(error) = history::append(&session)
+        {
+            warn!(%error, "could not save session to history");
+        }
+        self.history.push(session);
+    }
+
+    fn stop_all(&mut self, reason: StopReason, status: String) {
+        let sessions: Vec<Session> = self.clickers.iter_mut().filter_map(|clicker| clicker.stop(reason)).collect();
+        for session in sessions {
+            self.record(session);
+        }
+        // The script thread sees the stop flag and exits on its own.
+        if let Some(run) = self.scripts.run.take() {
+            run.stop();
+        }
+        info!(reason = %status, "stopped all");
+        self.status = status;
+    }
+
+    /// Text for the status display: running count, countdown, or the last stop message.
+    fn status_summary(&self) -> String {
+        let running = self.clickers.iter().filter(|clicker| clicker.is_running).count();
+        let countdown = self.clickers.iter().filter_map(|clicker| clicker.countdown).min();
+
+        match (running, countdown) {
+            (0, Some(seconds)) => format!("Starting in {}...", seconds),
+            (0, None) => match &self.scripts.run {
+                Some(run) => format!("Script {} - {} actions", run.name, run.actions()),
+                None => self.status.clone(),
+            },
+            (_, _) if self.clickers.len() == 1 => String::from("Running"),
+            (running, _) => format!("Running ({}/{})", running, self.clickers.len()),
+        }
+    }
+
+    fn preview_bundle(&mut self, bundle: Bundle) {
+        let local_macros = if self.environment.persist { Macro::list() } else { Vec::new() };
+        let plan = bundle.plan(&self.settings, &local_macros, self.bundle.replace);
+        self.bundle.preview = Some((bundle, plan));
+    }
+
+    /// Merges a previewed bundle. Replaced clickers that are running stop first.
+    fn import_bundle(&mut self, plan: &ImportPlan) {
+        let merged = match plan.merged(&self.settings) {
+            Ok(merged) => merged,
+            Err(error) => {
+                self.status = format!("Could not import bundle: {}", error);
+                return;
+            }
+        };
+        if self.environment.persist
+            && let Err(error) = plan.write_macros()
+        {
+            self.status = format!("Could not import macros: {}", error);
+            return;
+        }
+
+        self.settings = merged;
+        for (index, config) in self.settings.clickers.clone().into_iter().enumerate() {
+            if index >= self.clickers.len() {
+                self.clickers.push(Clicker::new(config, &self.environment));
+            } else if self.clickers[index].config != config {
+                self.stop_clicker(index, StopReason::Removed);
+                self.clickers[index] = Clicker::new(config, &self.environment);
+            }
+        }
+        hotkeys::set_script_stop_hotkey(&self.settings.scripts.stop_hotkey);
+        self.status = format!(
+            "Imported {} clickers and {} macros",
+            plan.clickers.len(),
+            plan.macros.len()
+        );
+        info!(clickers = plan.clickers.len(), macros = plan.macros.len(), "bundle imported");
+        self.save_settings();
+    }
+
+    /// Switches to settings edited outside the app. Interval changes reach
+    /// running clickers directly; a running clicker whose other settings
+    /// changed is restarted with them.
+    fn apply_settings(&mut self, mut settings: Settings) {
+        settings.migrate_legacy_clicker();
+        settings.assign_clicker_ids();
+        while self.clickers.len() > settings.clickers.len() {
+            let index = self.clickers.len() - 1;
+            self.stop_clicker(index, StopReason::Removed);
+            self.clickers.pop();
+        }
+        for (index, config) in settings.clickers.iter().enumerate() {
+            let Some(clicker) = self.clickers.get_mut(index) else {
+                self.clickers.push(Clicker::new(config.clone(), &self.environment));
+                continue;
+            };
+            if clicker.config == *config {
+                continue;
+            }
+            let interval_only = ClickerConfig { interval_ms: clicker.config.interval_ms, ..config.clone() } == clicker.config;
+            if interval_only {
+                clicker.config.interval_ms = config.interval_ms;
+                clicker.interval_input = config.interval_ms.to_string();
+                if clicker.is_running {
+                    clicker.engine.update_interval(config.interval_ms);
+                }
+                continue;
+            }
+            let was_running = clicker.is_running;
+            self.stop_clicker(index, StopReason::Reloaded);
+            self.clickers[index] = Clicker::new(config.clone(), &self.environment);
+            if was_running {
+                self.clickers[index].start();
+            }
+        }
+
+        apply_global_settings(&settings);
+        logging::set_level(settings.log_level);
+        if settings.theme_preference != self.theme_preference {
+            self.theme_preference = settings.theme_preference;
+            self.current_theme = resolve_theme(settings.theme_preference);
+            self.palette_modified = Palette::modified();
+        }
+        self.enable_dynamic_adjustment = settings.enable_dynamic_adjustment;
+        self.interval_step_mode = settings.interval_step_mode;
+        self.start_delay_input = settings.start_delay_secs.to_string();
+        if settings.schedules != self.settings.schedules {
+            self.scheduler = Scheduler::new(&settings.schedules, LocalNow::current());
+        }
+        self.active_clicker = self.active_clicker.min(self.clickers.len() - 1);
+        self.bundle.clickers.clear();
+        self.settings = settings;
+        self.settings_error = None;
+        info!("settings reloaded");
+        self.status = String::from("Settings reloaded");
+    }
+
+    /// Writes the clicker list back into `Settings`, persists it and refreshes
+    /// the global toggle hotkeys.
+    fn save_settings(&mut self) {
+        self.settings.clickers = self.clickers.iter().map(|clicker| clicker.config.clone()).collect();
+        hotkeys::set_toggle_hotkeys(&toggle_hotkeys(&self.settings.clickers));
+        if self.environment.persist && self.settings_error.is_none() {
+            let _ = self.settings.save();
+            // Our own write is not an outside edit.
+            self.settings_modified = Settings::modified();
+        }
+    }
+
+    /// Saves the mini strip position if it moved since the last save.
+    fn save_mini_position(&mut self) {
+        if self.mini_moved_at.take().is_some() {
+            self.save_settings();
+        }
+    }
+
+    /// Switches between the full window and the borderless always-on-top strip.
+    fn toggle_mini_mode(&mut self) -> Command<Message> {
+        self.save_mini_position();
+        self.mini_mode = !self.mini_mode;
+
+        let (size, level, position) = if self.mini_mode {
+            (ui::MINI_WINDOW_SIZE, window::Level::AlwaysOnTop, self.settings.mini_mode_position)
+        } else {
+            (ui::WINDOW_SIZE, window::Level::Normal, self.main_window_position)
+        };
+
+        let mut commands = vec![
+            window::toggle_decorations(window::Id::MAIN),
+            window::resize(window::Id::MAIN, size),
+            window::change_level(window::Id::MAIN, level),
+        ];
+        if let Some((x, y)) = position {
+            commands.push(window::move_to(window::Id::MAIN, Point::new(x as f32, y as f32)));
+        }
+        Command::batch(commands)
+    }
+
     fn adjust_interval(&mut self, delta: i32) {
         if !self.enable_dynamic_adjustment { return; }
 
-        let now = Instant::now();
+        let now = self.environment.clock.now();
         // Use a large default elapsed time on the first event to ensure a small multiplier.
         let elapsed_ms = self.last_scroll_time
             .map_or(1000.0, |last| now.duration_since(last).as_secs_f64() * 1000.0);
@@ -222,10 +1520,14 @@ impl SuperClicker {
         let multiplier = if elapsed_ms >= MAX_SENSITIVITY_THRESHOLD_MS {
             1.0
         } else {
-            (ACCELERATION_FACTOR / elapsed_ms).max(1.0).min(100.0)
+            (ACCELERATION_FACTOR / elapsed_ms).clamp(1.0, 100.0)
         };
 
-        let current_ms = self.interval_input.parse::<u64>().unwrap_or(100);
+        let index = self.active_clicker;
+        let Some(clicker) = self.clickers.get(index) else {
+            return;
+        };
+        let current_ms = clicker.interval_input.parse::<u64>().unwrap_or(100);
         
         // Normalize scroll delta. Some mice report large values.
         let base_change = if delta.abs() < 10 {
@@ -235,17 +1537,744 @@ impl SuperClicker {
         };
         
         // Apply the dynamic multiplier
-        let change = (base_change * multiplier).round() as i64;
-        
-        let new_ms_signed = (current_ms as i64) - change;
-        // Clamp the result to the allowed range (1ms to 10s).
-        let new_ms = new_ms_signed.max(1).min(10000) as u64;
+        let steps = base_change * multiplier;
+        let new_ms = step_interval(current_ms, steps, self.interval_step_mode);
 
-        if new_ms != current_ms {
-            self.interval_input = new_ms.to_string();
-            self.settings.interval_ms = new_ms;
-            let _ = self.settings.save();
-            self.clicking_engine.update_interval(new_ms);
+        if new_ms == current_ms {
+            return;
+        }
+        if self.needs_low_interval_confirmation(new_ms) {
+            // The strip is too small for the dialog; stop at the threshold instead.
+            if !self.mini_mode {
+                self.pending_low_interval = Some((index, new_ms));
+            }
+            return;
+        }
+        self.apply_interval(index, new_ms);
+    }
+
+    /// Asks for confirmation if the typed interval is below the warning
+    /// threshold and not applied yet. True if the dialog is now up.
+    fn ask_about_typed_interval(&mut self, index: usize) -> bool {
+        let Some(clicker) = self.clickers.get(index) else {
+            return false;
+        };
+        match clicker.interval_input.parse::<u64>() {
+            Ok(ms) if ms > 0 && ms != clicker.config.interval_ms && self.needs_low_interval_confirmation(ms) => {
+                self.pending_low_interval = Some((index, ms));
+                true
+            }
+            _ => false,
         }
     }
-}
\ No newline at end of file
+
+    /// True the first time the user goes below the warning threshold.
+    fn needs_low_interval_confirmation(&self, ms: u64) -> bool {
+        let limits = &self.settings.rate_limit;
+        !limits.low_interval_confirmed && ms < limits.warn_below_ms
+    }
+
+    fn apply_interval(&mut self, index: usize, ms: u64) {
+        let Some(clicker) = self.clickers.get_mut(index) else {
+            return;
+        };
+        clicker.interval_input = ms.to_string();
+        clicker.config.interval_ms = ms;
+        clicker.engine.update_interval(ms);
+        self.active_clicker = index;
+        self.save_settings();
+    }
+}
+
+/// Hands the settings the engine and listeners read to them.
+fn apply_global_settings(settings: &Settings) {
+    hotkeys::set_fail_safe(settings.fail_safe);
+    hotkeys::set_auto_stop(settings.auto_stop);
+    clicking::set_timing_offset(Duration::from_micros(settings.timing_offset_us));
+    clicking::set_max_cps(settings.rate_limit.max_cps);
+    hotkeys::set_toggle_hotkeys(&toggle_hotkeys(&settings.clickers));
+    hotkeys::set_script_stop_hotkey(&settings.scripts.stop_hotkey);
+}
+
+/// "Clicker N" with the lowest N not taken, since schedules, macros and
+/// bundles find clickers by name.
+fn free_clicker_name(clickers: &[Clicker]) -> String {
+    (1..)
+        .map(|number| format!("Clicker {}", number))
+        .find(|name| clickers.iter().all(|clicker| clicker.config.name != *name))
+        .unwrap_or_default()
+}
+
+fn toggle_hotkeys(clickers: &[ClickerConfig]) -> Vec<Hotkey> {
+    clickers.iter().map(|clicker| clicker.hotkey.clone()).collect()
+}
+
+/// Text shown in a pattern step's value field.
+fn step_input(step: &PatternStep) -> String {
+    match step {
+        PatternStep::Wait(ms) => ms.to_string(),
+        PatternStep::MoveTo(x, y) => format!("{}, {}", x, y),
+        PatternStep::Act(Action::Press(key)) => key.clone(),
+        PatternStep::Act(Action::Scroll(_, notches)) => notches.to_string(),
+        PatternStep::Act(Action::Click(_)) => String::new(),
+    }
+}
+
+/// Notches a scroll action turns; other actions start at one if switched to scrolling.
+fn scroll_notches(action: &Action) -> u32 {
+    match action {
+        Action::Scroll(_, notches) => *notches,
+        _ => 1,
+    }
+}
+
+/// Text shown in a clicker's movement field.
+fn motion_input(motion: &Motion) -> String {
+    match motion {
+        Motion::Still => String::new(),
+        Motion::Jiggle { pixels } => pixels.to_string(),
+        Motion::Path { points, .. } => points
+            .iter()
+            .map(|(x, y)| format!("{}, {}", x, y))
+            .collect::<Vec<_>>()
+            .join("; "),
+        Motion::Circle { center, radius, .. } => format!("{}, {}, {}", center.0, center.1, radius),
+    }
+}
+
+fn motion_speed(motion: &Motion) -> Option<u32> {
+    match motion {
+        Motion::Path { speed, .. } | Motion::Circle { speed, .. } => Some(*speed),
+        Motion::Still | Motion::Jiggle { .. } => None,
+    }
+}
+
+/// Applies an edited movement field; returns false while the text doesn't parse.
+fn apply_motion_input(motion: &mut Motion, value: &str) -> bool {
+    match motion {
+        Motion::Still => false,
+        Motion::Jiggle { pixels } => match value.trim().parse() {
+            Ok(value) => {
+                *pixels = value;
+                true
+            }
+            Err(_) => false,
+        },
+        Motion::Path { points, .. } => {
+            let parsed: Option<Vec<(i32, i32)>> = value.split(';').map(parse_point).collect();
+            match parsed {
+                Some(parsed) => {
+                    *points = parsed;
+                    true
+                }
+                None => false,
+            }
+        }
+        Motion::Circle { center, radius, .. } => {
+            let parts: Vec<&str> = value
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|part| !part.is_empty())
+                .collect();
+            match parts.as_slice() {
+                [x, y, r] => match (x.parse(), y.parse(), r.parse()) {
+                    (Ok(x), Ok(y), Ok(r)) => {
+                        *center = (x, y);
+                        *radius = r;
+                        true
+                    }
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Stores known keys under their canonical name ("f6" -> "F6"), anything else as typed.
+fn canonical_key_name(value: String) -> String {
+    keys::parse_key(&value)
+        .and_then(keys::key_name)
+        .map_or(value, String::from)
+}
+
+/// Parses a screen position written as "x, y" or "x y".
+fn parse_point(value: &str) -> Option<(i32, i32)> {
+    let mut parts = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty());
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((x, y))
+}
+
+fn window_event(event: Event, _status: event::Status) -> Option<Message> {
+    match event {
+        Event::Window(_, window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+        Event::Window(_, window::Event::CloseRequested) => Some(Message::CloseRequested),
+        Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => Some(Message::DragEnded),
+        _ => None,
+    }
+}
+
+/// Picks the theme for a preference, asking the OS when it is `System`.
+fn resolve_theme(preference: ThemePreference) -> AppTheme {
+    match preference {
+        ThemePreference::System => system_theme::detect(),
+        ThemePreference::Light => AppTheme::Light,
+        ThemePreference::Dark => AppTheme::Dark,
+        ThemePreference::HighContrast => AppTheme::HighContrast,
+        ThemePreference::Custom => match Palette::load() {
+            Ok(palette) => {
+                let theme = AppTheme::Custom(palette);
+                warn_low_contrast(theme);
+                theme
+            }
+            Err(error) => {
+                // Seed a palette file from the system theme so there is something to edit.
+                let fallback = system_theme::detect();
+                if Palette::path().is_ok_and(|path| !path.exists()) {
+                    let _ = fallback.palette().save();
+                } else {
+                    warn!(%error, "could not load palette");
+                }
+                fallback
+            }
+        },
+    }
+}
+
+/// User palettes are not checked at build time, so report weak pairs on load.
+fn warn_low_contrast(theme: AppTheme) {
+    for failure in theme.contrast_failures() {
+        warn!(%failure, "palette contrast too low");
+    }
+}
+
+/// Percentage applied per step in `IntervalStepMode::Proportional`.
+const PROPORTIONAL_STEP_RATIO: f64 = 0.05;
+
+/// How much of the log file the in-app viewer shows.
+const LOG_VIEWER_LINES: usize = 200;
+
+/// Newest sessions listed in the history card.
+const HISTORY_VIEW_SESSIONS: usize = 20;
+
+/// How long the mini strip must stay put before its position is saved.
+const MINI_POSITION_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// "2026-10-19 10:30  Clicker 1: 2m 5s, 1255 clicks (10.0/s), hotkey"
+fn describe_session(session: &Session, offset_secs: i32) -> String {
+    let secs = session.duration_ms / 1000;
+    let duration = if secs >= 3600 {
+        format!("{}h {}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    };
+    format!(
+        "{}  {}: {}, {} clicks ({:.1}/s), {}",
+        history::format_local(session.started, offset_secs),
+        session.clicker,
+        duration,
+        session.clicks,
+        session.cps(),
+        session.stop_reason
+    )
+}
+
+/// Applies `steps` interval steps to `current_ms`. Positive steps shorten the
+/// interval (scroll up / arrow up speeds clicking up). The result is clamped to
+/// the allowed range (1ms to 10s).
+fn step_interval(current_ms: u64, steps: f64, mode: IntervalStepMode) -> u64 {
+    let change = match mode {
+        IntervalStepMode::Linear => steps.round() as i64,
+        IntervalStepMode::Proportional => {
+            // Geometric scaling keeps each step the same *relative* size across the range.
+            let target = current_ms as f64 * (1.0 + PROPORTIONAL_STEP_RATIO).powf(-steps);
+            let change = current_ms as i64 - target.round() as i64;
+            // Small intervals would otherwise round back to themselves and get stuck.
+            if change == 0 && steps != 0.0 {
+                steps.signum() as i64
+            } else {
+                change
+            }
+        }
+    };
+
+    let new_ms_signed = (current_ms as i64) - change;
+    new_ms_signed.clamp(1, 10000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use super_clicker::clock::ManualClock;
+    use super_clicker::settings::ScrollDirection;
+
+    /// What the app asked of its engines, shared by every `MockEngine`.
+    #[derive(Debug, Default)]
+    struct EngineLog {
+        starts: u32,
+        stops: u32,
+        interval_ms: Option<u64>,
+    }
+
+    struct MockEngine {
+        log: Arc<Mutex<EngineLog>>,
+    }
+
+    impl ClickerEngine for MockEngine {
+        fn start(&self, config: &ClickerConfig) {
+            let mut log = self.log.lock().unwrap();
+            log.starts += 1;
+            log.interval_ms = Some(config.interval_ms);
+        }
+
+        fn stop(&self) {
+            self.log.lock().unwrap().stops += 1;
+        }
+
+        fn update_interval(&self, new_ms: u64) {
+            self.log.lock().unwrap().interval_ms = Some(new_ms);
+        }
+
+        fn click_count(&self) -> u64 {
+            0
+        }
+    }
+
+    struct Harness {
+        app: SuperClicker,
+        clock: Arc<ManualClock>,
+        log: Arc<Mutex<EngineLog>>,
+    }
+
+    impl Harness {
+        fn send(&mut self, messages: impl IntoIterator<Item = Message>) {
+            for message in messages {
+                let _ = self.app.update(message);
+            }
+        }
+
+        fn advance_ms(&self, ms: u64) {
+            self.clock.advance(Duration::from_millis(ms));
+        }
+
+        fn interval(&self) -> u64 {
+            self.app.clickers[0].config.interval_ms
+        }
+    }
+
+    fn harness(configure: impl FnOnce(&mut Settings)) -> Harness {
+        let mut settings = Settings {
+            // Avoid asking the OS for its theme.
+            theme_preference: ThemePreference::Dark,
+            start_delay_secs: 0,
+            ..Settings::default()
+        };
+        configure(&mut settings);
+
+        let clock = Arc::new(ManualClock::new());
+        let log = Arc::new(Mutex::new(EngineLog::default()));
+        let engine_log = Arc::clone(&log);
+        let environment = Environment {
+            clock: clock.clone(),
+            new_engine: Box::new(move || Box::new(MockEngine { log: Arc::clone(&engine_log) })),
+            persist: false,
+        };
+
+        Harness {
+            app: SuperClicker::with_environment(settings, environment),
+            clock,
+            log,
+        }
+    }
+
+    #[test]
+    fn toggle_hotkey_is_debounced() {
+        let mut h = harness(|_| {});
+        h.send([Message::ToggleFromHotkey(0)]);
+        assert!(h.app.clickers[0].is_running);
+
+        // Key repeat or a bouncing switch.
+        h.advance_ms(100);
+        h.send([Message::ToggleFromHotkey(0)]);
+        assert!(h.app.clickers[0].is_running);
+
+        h.advance_ms(300);
+        h.send([Message::ToggleFromHotkey(0)]);
+        assert!(!h.app.clickers[0].is_running);
+
+        let log = h.log.lock().unwrap();
+        assert_eq!((log.starts, log.stops), (1, 1));
+    }
+
+    #[test]
+    fn countdown_starts_clicker_after_delay() {
+        let mut h = harness(|settings| settings.start_delay_secs = 2);
+        h.send([Message::StartClicker(0)]);
+        assert_eq!(h.app.clickers[0].countdown, Some(2));
+        assert!(!h.app.clickers[0].is_running);
+
+        h.send([Message::CountdownTick]);
+        assert_eq!(h.app.clickers[0].countdown, Some(1));
+
+        h.send([Message::CountdownTick]);
+        assert_eq!(h.app.clickers[0].countdown, None);
+        assert!(h.app.clickers[0].is_running);
+        assert_eq!(h.log.lock().unwrap().starts, 1);
+    }
+
+    #[test]
+    fn toggle_hotkey_cancels_countdown() {
+        let mut h = harness(|settings| settings.start_delay_secs = 3);
+        h.send([Message::ToggleFromHotkey(0)]);
+        assert_eq!(h.app.clickers[0].countdown, Some(3));
+
+        h.advance_ms(500);
+        h.send([Message::ToggleFromHotkey(0), Message::CountdownTick, Message::CountdownTick, Message::CountdownTick]);
+        assert!(!h.app.clickers[0].is_active());
+        assert_eq!(h.log.lock().unwrap().starts, 0);
+    }
+
+    #[test]
+    fn slow_scrolls_step_by_one_and_fast_scrolls_accelerate() {
+        let mut h = harness(|settings| settings.interval_ms = 100);
+        h.send([Message::IntervalChange(1)]);
+        assert_eq!(h.interval(), 99);
+
+        // 150 / 15 ms gives a 10x multiplier.
+        h.advance_ms(15);
+        h.send([Message::IntervalChange(1)]);
+        assert_eq!(h.interval(), 89);
+
+        h.advance_ms(1000);
+        h.send([Message::IntervalChange(-1)]);
+        assert_eq!(h.interval(), 90);
+        assert_eq!(h.log.lock().unwrap().interval_ms, Some(90));
+    }
+
+    #[test]
+    fn proportional_mode_steps_by_percentage() {
+        let mut h = harness(|settings| {
+            settings.interval_ms = 100;
+            settings.interval_step_mode = IntervalStepMode::Proportional;
+        });
+        h.send([Message::IntervalChange(1)]);
+        assert_eq!(h.interval(), 95);
+    }
+
+    #[test]
+    fn interval_change_ignored_when_dynamic_adjustment_disabled() {
+        let mut h = harness(|settings| {
+            settings.interval_ms = 100;
+            settings.enable_dynamic_adjustment = false;
+        });
+        h.send([Message::IntervalChange(1), Message::IntervalChange(120)]);
+        assert_eq!(h.interval(), 100);
+        assert_eq!(h.log.lock().unwrap().interval_ms, None);
+    }
+
+    #[test]
+    fn edited_interval_reaches_engine() {
+        let mut h = harness(|_| {});
+        h.send([Message::ClickerIntervalChanged(0, String::from("250"))]);
+        assert_eq!(h.interval(), 250);
+        assert_eq!(h.log.lock().unwrap().interval_ms, Some(250));
+
+        // Half-typed values leave the interval alone.
+        h.send([Message::ClickerIntervalChanged(0, String::from("25x"))]);
+        assert_eq!(h.interval(), 250);
+    }
+
+    #[test]
+    fn stopping_records_a_session() {
+        let mut h = harness(|_| {});
+        // Cancelling a countdown is not a session.
+        h.app.settings.start_delay_secs = 3;
+        h.send([Message::StartClicker(0), Message::StopClicker(0)]);
+        assert!(h.app.history.is_empty());
+
+        h.app.settings.start_delay_secs = 0;
+        h.send([Message::ToggleFromHotkey(0)]);
+        h.advance_ms(2500);
+        h.send([Message::ToggleFromHotkey(0)]);
+
+        let [session] = h.app.history.as_slice() else {
+            panic!("expected one session, got {:?}", h.app.history);
+        };
+        assert_eq!(session.clicker, "Clicker 1");
+        assert_eq!(session.duration_ms, 2500);
+        assert_eq!(session.stop_reason, StopReason::Hotkey);
+    }
+
+    #[test]
+    fn low_interval_needs_confirmation_once() {
+        let mut h = harness(|_| {});
+        h.send([Message::ClickerIntervalChanged(0, String::from("0"))]);
+        assert_eq!(h.interval(), 100);
+        assert_eq!(h.app.pending_low_interval, None);
+
+        h.send([Message::ClickerIntervalChanged(0, String::from("5"))]);
+        assert_eq!(h.interval(), 100);
+        assert_eq!(h.app.pending_low_interval, None, "only asked once submitted");
+
+        h.send([Message::ClickerIntervalSubmitted(0)]);
+        assert_eq!(h.app.pending_low_interval, Some((0, 5)));
+
+        h.send([Message::CancelLowInterval]);
+        assert_eq!(h.app.clickers[0].interval_input, "100");
+
+        // Starting with a low value typed asks too, and doesn't start yet.
+        h.send([Message::ClickerIntervalChanged(0, String::from("5")), Message::StartClicker(0)]);
+        assert!(!h.app.clickers[0].is_running);
+        h.send([Message::ConfirmLowInterval]);
+        assert_eq!(h.interval(), 5);
+        assert_eq!(h.log.lock().unwrap().interval_ms, Some(5));
+
+        h.send([Message::ClickerIntervalChanged(0, String::from("2"))]);
+        assert_eq!(h.interval(), 2);
+        assert_eq!(h.app.pending_low_interval, None);
+    }
+
+    #[test]
+    fn typing_a_normal_interval_does_not_ask() {
+        let mut h = harness(|_| {});
+        for value in ["1", "15", "150"] {
+            h.send([Message::ClickerIntervalChanged(0, String::from(value))]);
+            assert_eq!(h.app.pending_low_interval, None, "asked at {}", value);
+        }
+        h.send([Message::ClickerIntervalSubmitted(0)]);
+        assert_eq!(h.app.pending_low_interval, None);
+        assert_eq!(h.interval(), 150);
+    }
+
+    #[test]
+    fn scrolling_below_threshold_asks_first() {
+        let mut h = harness(|settings| settings.interval_ms = 20);
+        h.send([Message::IntervalChange(1)]);
+        assert_eq!(h.interval(), 20);
+        assert_eq!(h.app.pending_low_interval, Some((0, 19)));
+    }
+
+    #[test]
+    fn auto_stop_reports_reason_in_status_and_history() {
+        let mut h = harness(|_| {});
+        h.send([Message::AutoStop(StopReason::Idle)]);
+        assert_eq!(h.app.status, "Stopped");
+
+        h.send([Message::StartClicker(0), Message::AutoStop(StopReason::Locked)]);
+        assert!(!h.app.clickers[0].is_running);
+        assert_eq!(h.app.status, "Stopped (computer locked)");
+        assert_eq!(h.app.history.last().map(|session| session.stop_reason), Some(StopReason::Locked));
+    }
+
+    #[test]
+    fn runs_started_while_idle_stop_at_the_next_check() {
+        let mut h = harness(|_| {});
+        h.app.settings.schedules.push(Schedule {
+            clicker_id: h.app.clickers[0].config.id,
+            clicker: None,
+            kind: ScheduleKind::Every { minutes: 5 },
+            run_secs: 0,
+            enabled: true,
+        });
+        h.send([Message::AutoStop(StopReason::Idle)]);
+
+        // The user is still away when the schedule fires.
+        h.app.run_schedule_event(ScheduleEvent::Start(0));
+        assert!(h.app.clickers[0].is_running);
+        h.send([Message::AutoStop(StopReason::Idle)]);
+        assert!(!h.app.clickers[0].is_running);
+        assert_eq!(h.app.history.last().map(|session| session.stop_reason), Some(StopReason::Idle));
+    }
+
+    #[test]
+    fn mini_position_is_saved_once_the_strip_stops_moving() {
+        let mut h = harness(|_| {});
+        h.send([Message::ToggleMiniMode, Message::WindowMoved(10, 20), Message::WindowMoved(15, 25)]);
+        h.advance_ms(250);
+        h.send([Message::CheckMiniPosition]);
+        assert!(h.app.mini_moved_at.is_some(), "still moving");
+
+        // No button release reached us, but the strip has been still long enough.
+        h.advance_ms(300);
+        h.send([Message::CheckMiniPosition]);
+        assert_eq!(h.app.mini_moved_at, None);
+        assert_eq!(h.app.settings.mini_mode_position, Some((15, 25)));
+    }
+
+    #[test]
+    fn fail_safe_stops_every_clicker() {
+        let mut h = harness(|_| {});
+        h.send([Message::AddClicker, Message::StartClicker(0), Message::StartClicker(1)]);
+        assert!(h.app.clickers.iter().all(|clicker| clicker.is_running));
+
+        h.send([Message::FailSafe(FailSafeTrigger::EscapeRepeated)]);
+        assert!(h.app.clickers.iter().all(|clicker| !clicker.is_running));
+        assert!(h.app.status.starts_with("Stopped"));
+        assert_eq!(h.log.lock().unwrap().stops, 2);
+    }
+
+    #[test]
+    fn scroll_notches_survive_direction_changes() {
+        let mut h = harness(|_| {});
+        h.send([
+            Message::ClickerActionSelected(0, ActionKind::ScrollDown),
+            Message::ClickerScrollChanged(0, String::new()),
+            Message::ClickerScrollChanged(0, String::from("3")),
+            Message::ClickerActionSelected(0, ActionKind::ScrollUp),
+        ]);
+        assert_eq!(h.app.clickers[0].config.action, Action::Scroll(ScrollDirection::Up, 3));
+        assert_eq!(h.app.clickers[0].scroll_input, "3");
+
+        h.send([Message::ClickerScrollChanged(0, String::from("0"))]);
+        assert_eq!(h.app.clickers[0].config.action, Action::Scroll(ScrollDirection::Up, 3));
+        h.send([Message::ClickerScrollChanged(0, String::from("274"))]);
+        assert_eq!(h.app.clickers[0].config.action, Action::Scroll(ScrollDirection::Up, 3));
+    }
+
+    #[test]
+    fn movement_fields_parse_per_kind() {
+        let mut h = harness(|_| {});
+        h.send([
+            Message::ClickerMotionSelected(0, MotionKind::Path),
+            Message::ClickerMotionChanged(0, String::from("10, 20; 30 40")),
+            Message::ClickerMotionSpeedChanged(0, String::from("250")),
+        ]);
+        assert_eq!(
+            h.app.clickers[0].config.motion,
+            Motion::Path { points: vec![(10, 20), (30, 40)], speed: 250 }
+        );
+
+        // Half-typed text leaves the last good value in place.
+        h.send([Message::ClickerMotionChanged(0, String::from("10, 20; 30"))]);
+        assert_eq!(h.app.clickers[0].config.motion.kind(), MotionKind::Path);
+        assert_eq!(motion_input(&h.app.clickers[0].config.motion), "10, 20; 30, 40");
+
+        h.send([
+            Message::ClickerMotionSelected(0, MotionKind::Circle),
+            Message::ClickerMotionChanged(0, String::from("400, 300, 80")),
+        ]);
+        assert_eq!(
+            h.app.clickers[0].config.motion,
+            Motion::Circle { center: (400, 300), radius: 80, speed: 250 }
+        );
+        assert_eq!(h.app.clickers[0].speed_input, "250");
+    }
+
+    #[test]
+    fn two_captured_clicks_set_the_area() {
+        let mut h = harness(|_| {});
+        // Clicks outside a capture are ignored.
+        h.send([Message::PointCaptured(1, 1)]);
+        assert_eq!(h.app.clickers[0].config.area, None);
+
+        h.send([
+            Message::CaptureArea(0),
+            Message::PointCaptured(100, 200),
+            Message::PointCaptured(140, 260),
+        ]);
+        let area = h.app.clickers[0].config.area.expect("area captured");
+        assert_eq!((area.shape, area.from, area.to), (AreaShape::Rectangle, (100, 200), (140, 260)));
+        assert!(h.app.clickers[0].capture.is_none());
+
+        h.send([Message::AreaShapeSelected(0, AreaShape::Circle)]);
+        let area = h.app.clickers[0].config.area.unwrap();
+        assert_eq!((area.from, area.to), ((120, 230), (140, 230)));
+    }
+
+    #[test]
+    fn bundle_import_previews_then_merges() {
+        let mut h = harness(|_| {});
+        let mut shared = h.app.settings.clickers[0].clone();
+        shared.interval_ms = 250;
+        let bundle = Bundle {
+            version: bundle::BUNDLE_VERSION,
+            clickers: vec![shared],
+            ..Bundle::default()
+        };
+
+        h.app.preview_bundle(bundle);
+        assert_eq!(h.app.clickers.len(), 1, "previewing changes nothing");
+        h.send([Message::BundleReplaceToggled(true), Message::ImportBundle]);
+        assert_eq!(h.app.clickers.len(), 1);
+        assert_eq!(h.app.clickers[0].config.interval_ms, 250);
+        assert!(h.app.bundle.preview.is_none());
+    }
+
+    #[test]
+    fn edited_settings_apply_live() {
+        let mut h = harness(|_| {});
+        h.send([Message::StartClicker(0)]);
+
+        // An interval edit reaches the running engine without a restart.
+        let mut edited = h.app.settings.clone();
+        edited.clickers[0].interval_ms = 40;
+        h.app.apply_settings(edited.clone());
+        assert!(h.app.clickers[0].is_running);
+        assert_eq!(h.app.clickers[0].interval_input, "40");
+        let log = h.log.lock().unwrap();
+        assert_eq!((log.starts, log.interval_ms), (1, Some(40)));
+        drop(log);
+
+        // Other changes restart it; the theme follows too.
+        edited.clickers[0].action = Action::Press(String::from("Space"));
+        edited.theme_preference = ThemePreference::Light;
+        h.app.apply_settings(edited);
+        assert!(h.app.clickers[0].is_running);
+        assert_eq!(h.log.lock().unwrap().starts, 2);
+        assert_eq!(h.app.history.last().map(|session| session.stop_reason), Some(StopReason::Reloaded));
+        assert_eq!(h.app.current_theme, AppTheme::Light);
+    }
+
+    #[test]
+    fn added_clickers_get_a_free_name() {
+        let mut h = harness(|_| {});
+        h.send([Message::AddClicker, Message::RemoveClicker(0), Message::AddClicker]);
+        let names: Vec<&str> = h.app.clickers.iter().map(|clicker| clicker.config.name.as_str()).collect();
+        assert_eq!(names, ["Clicker 2", "Clicker 1"]);
+    }
+
+    #[test]
+    fn schedules_follow_a_renamed_clicker() {
+        let mut h = harness(|_| {});
+        h.app.settings.schedules.push(Schedule {
+            clicker_id: h.app.clickers[0].config.id,
+            clicker: None,
+            kind: ScheduleKind::Every { minutes: 5 },
+            run_secs: 0,
+            enabled: true,
+        });
+        h.send([Message::AddClicker, Message::ClickerNameChanged(0, String::from("Clicker 2"))]);
+
+        h.app.run_schedule_event(ScheduleEvent::Start(0));
+        assert!(h.app.clickers[0].is_running);
+        assert!(!h.app.clickers[1].is_running);
+    }
+
+    #[test]
+    fn schedules_only_stop_runs_they_started() {
+        let mut h = harness(|_| {});
+        h.app.settings.schedules.push(Schedule {
+            clicker_id: h.app.clickers[0].config.id,
+            clicker: None,
+            kind: ScheduleKind::Every { minutes: 5 },
+            run_secs: 30,
+            enabled: true,
+        });
+
+        h.send([Message::StartClicker(0)]);
+        h.app.run_schedule_event(ScheduleEvent::Start(0));
+        h.app.run_schedule_event(ScheduleEvent::Stop(0));
+        assert!(h.app.clickers[0].is_running, "the user's own run was stopped");
+
+        h.send([Message::StopClicker(0)]);
+        h.app.run_schedule_event(ScheduleEvent::Start(0));
+        h.app.run_schedule_event(ScheduleEvent::Stop(0));
+        assert!(!h.app.clickers[0].is_running);
+        assert_eq!(h.app.history.last().map(|session| session.stop_reason), Some(StopReason::Schedule));
+    }
+}
@@ -0,0 +1,258 @@
+//! Timing accuracy benchmark.
+//!
+//! Runs a real `ClickingEngine` against a `MockBackend` at several intervals
+//! and measures how far the time between clicks drifts from the target. The
+//! median overshoot becomes the suggested calibration offset
+//! (`Settings::timing_offset_us`), which engines subtract from every sleep.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::clicking::{self, ClickingEngine, MockBackend};
+use super::settings::{ClickerConfig, Settings};
+
+/// Intervals measured, in milliseconds.
+pub const BANDS_MS: [u64; 5] = [1, 10, 50, 100, 250];
+
+/// Gaps between clicks measured per band.
+const BAND_SAMPLES: usize = 40;
+
+/// A band stops early after this long, so slow bands don't drag on.
+const MAX_BAND_TIME: Duration = Duration::from_secs(3);
+
+/// Timing of one interval band. Drift is the measured gap between two clicks
+/// minus the interval, in milliseconds; positive means late.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandResult {
+    pub interval_ms: u64,
+    pub samples: usize,
+    pub mean_drift_ms: f64,
+    pub p50_drift_ms: f64,
+    pub p99_drift_ms: f64,
+    /// Standard deviation of the gaps.
+    pub jitter_ms: f64,
+}
+
+impl BandResult {
+    /// Computes drift and jitter statistics from measured gaps.
+    pub fn from_gaps(interval_ms: u64, gaps: &[Duration]) -> Self {
+        let mut drifts: Vec<f64> = gaps
+            .iter()
+            .map(|gap| gap.as_secs_f64() * 1000.0 - interval_ms as f64)
+            .collect();
+        drifts.sort_by(f64::total_cmp);
+
+        let count = drifts.len().max(1) as f64;
+        let mean_drift_ms = drifts.iter().sum::<f64>() / count;
+        // The gaps' spread equals the drifts' spread.
+        let variance = drifts.iter().map(|drift| (drift - mean_drift_ms).powi(2)).sum::<f64>() / count;
+
+        BandResult {
+            interval_ms,
+            samples: drifts.len(),
+            mean_drift_ms,
+            p50_drift_ms: percentile(&drifts, 0.50),
+            p99_drift_ms: percentile(&drifts, 0.99),
+            jitter_ms: variance.sqrt(),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Results of a benchmark run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Calibration offset in effect while measuring.
+    pub offset_us: u64,
+    pub bands: Vec<BandResult>,
+}
+
+impl Report {
+    /// Offset that cancels the median overshoot measured here, on top of the
+    /// offset that was already in effect. Bands shorter than the press hold
+    /// can never be met and are left out.
+    pub fn suggested_offset_us(&self) -> u64 {
+        let hold_ms = clicking::PRESS_HOLD.as_millis() as u64;
+        let mut drifts: Vec<f64> = self
+            .bands
+            .iter()
+            .filter(|band| band.interval_ms > hold_ms && band.samples > 0)
+            .map(|band| band.p50_drift_ms)
+            .collect();
+        drifts.sort_by(f64::total_cmp);
+        let median_ms = percentile(&drifts, 0.50);
+        (self.offset_us as f64 + median_ms * 1000.0).max(0.0).round() as u64
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Drift in ms (calibration offset {} us)", self.offset_us)?;
+        writeln!(f, "{:>9} {:>8} {:>8} {:>8} {:>8}", "interval", "mean", "p50", "p99", "jitter")?;
+        for band in &self.bands {
+            writeln!(
+                f,
+                "{:>6} ms {:>8.3} {:>8.3} {:>8.3} {:>8.3}",
+                band.interval_ms, band.mean_drift_ms, band.p50_drift_ms, band.p99_drift_ms, band.jitter_ms
+            )?;
+        }
+        write!(f, "Suggested offset: {} us", self.suggested_offset_us())
+    }
+}
+
+/// Measures every band in `BANDS_MS`. Takes a few seconds.
+pub fn run() -> Report {
+    Report {
+        offset_us: clicking::timing_offset().as_micros() as u64,
+        bands: BANDS_MS.iter().map(|&interval_ms| measure_band(interval_ms)).collect(),
+    }
+}
+
+fn measure_band(interval_ms: u64) -> BandResult {
+    let backend = Arc::new(MockBackend::new());
+    // Uncapped, or the short bands would measure the CPS cap instead of drift.
+    let engine = ClickingEngine::with_backend(backend.clone()).uncapped();
+    let config = ClickerConfig {
+        name: String::from("Benchmark"),
+        interval_ms,
+        ..ClickerConfig::default()
+    };
+
+    let started = Instant::now();
+    engine.start(&config);
+    while backend.press_times().len() <= BAND_SAMPLES && started.elapsed() < MAX_BAND_TIME {
+        thread::sleep(Duration::from_millis(5));
+    }
+    engine.stop();
+
+    let presses = backend.press_times();
+    let gaps: Vec<Duration> = presses.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    BandResult::from_gaps(interval_ms, &gaps)
+}
+
+/// A benchmark running on its own thread, started from the UI.
+pub struct BenchmarkRun {
+    report: Arc<Mutex<Option<Report>>>,
+}
+
+impl BenchmarkRun {
+    pub fn start() -> Self {
+        let report = Arc::new(Mutex::new(None));
+        let thread_report = Arc::clone(&report);
+        thread::spawn(move || {
+            let result = run();
+            if let Ok(mut report) = thread_report.lock() {
+                *report = Some(result);
+            }
+        });
+        BenchmarkRun { report }
+    }
+
+    /// The report, once the run is done.
+    pub fn report(&self) -> Option<Report> {
+        self.report.lock().ok().and_then(|report| report.clone())
+    }
+}
+
+/// Runs the benchmark without opening a window (`--benchmark`), printing the
+/// report. With `calibrate`, saves the suggested offset; a settings file that
+/// can't be read is left alone. Returns the process exit code.
+pub fn run_from_cli(calibrate: bool) -> i32 {
+    let mut settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(error) if calibrate => {
+            eprintln!("Could not read settings, not calibrating: {}", error);
+            return 1;
+        }
+        Err(error) => {
+            warn!(%error, "could not read settings, benchmarking with defaults");
+            Settings::default()
+        }
+    };
+    clicking::set_timing_offset(Duration::from_micros(settings.timing_offset_us));
+    clicking::set_max_cps(settings.rate_limit.max_cps);
+
+    let report = run();
+    info!(suggested_offset_us = report.suggested_offset_us(), "benchmark finished");
+    println!("{}", report);
+
+    if calibrate {
+        settings.timing_offset_us = report.suggested_offset_us();
+        if let Err(error) = settings.save() {
+            eprintln!("Could not save calibration: {}", error);
+            return 1;
+        }
+        println!("Saved calibration offset of {} us", settings.timing_offset_us);
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band(interval_ms: u64, p50_drift_ms: f64) -> BandResult {
+        BandResult {
+            interval_ms,
+            samples: 10,
+            mean_drift_ms: p50_drift_ms,
+            p50_drift_ms,
+            p99_drift_ms: p50_drift_ms,
+            jitter_ms: 0.0,
+        }
+    }
+
+    #[test]
+    fn drift_statistics_from_gaps() {
+        let gaps: Vec<Duration> = [10, 11, 11, 12, 20].iter().map(|&ms| Duration::from_millis(ms)).collect();
+        let result = BandResult::from_gaps(10, &gaps);
+        assert_eq!(result.samples, 5);
+        assert!((result.mean_drift_ms - 2.8).abs() < 1e-9);
+        assert!((result.p50_drift_ms - 1.0).abs() < 1e-9);
+        assert!((result.p99_drift_ms - 10.0).abs() < 1e-9);
+        assert!((result.jitter_ms - 3.655).abs() < 0.001);
+    }
+
+    #[test]
+    fn suggestion_ignores_bands_below_press_hold() {
+        let report = Report {
+            offset_us: 200,
+            bands: vec![band(1, 9.0), band(50, 0.5), band(100, 0.75), band(250, 0.25)],
+        };
+        assert_eq!(report.suggested_offset_us(), 700);
+
+        // Running early pulls an existing offset back down, but never below zero.
+        let early = Report {
+            offset_us: 200,
+            bands: vec![band(100, -0.5)],
+        };
+        assert_eq!(early.suggested_offset_us(), 0);
+    }
+
+    #[test]
+    fn engine_clicks_against_mock_backend() {
+        let backend = Arc::new(MockBackend::new());
+        let engine = ClickingEngine::with_backend(backend.clone());
+        engine.start(&ClickerConfig {
+            interval_ms: 20,
+            ..ClickerConfig::default()
+        });
+        thread::sleep(Duration::from_millis(150));
+        engine.stop();
+
+        let presses = backend.press_times();
+        assert!(presses.len() >= 3, "only {} clicks", presses.len());
+        assert!(presses.windows(2).all(|pair| pair[1] - pair[0] >= Duration::from_millis(15)));
+    }
+}
@@ -0,0 +1,407 @@
+//! Sharing a setup as one file.
+//!
+//! A bundle holds chosen clickers, macro files and the script stop hotkey.
+//! Bundles are JSON files in the `bundles` folder of the config directory.
+//! Importing first builds an `ImportPlan` that shows what will be added,
+//! renamed or replaced and which hotkeys clash, then merges it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::keys;
+use super::macros::{self, Macro};
+use super::settings::{ClickerConfig, Hotkey, Settings};
+
+const BUNDLE_EXTENSION: &str = "json";
+
+/// Bumped when the format changes in a way older versions can't read.
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bundle {
+    pub version: u32,
+    pub clickers: Vec<ClickerConfig>,
+    /// Macro text by name, as in the macros folder.
+    pub macros: BTreeMap<String, String>,
+    /// `None` when hotkeys were left out of the export.
+    pub script_stop_hotkey: Option<Hotkey>,
+}
+
+pub fn bundles_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(Settings::config_dir()?.join("bundles"))
+}
+
+/// Names (without extension) of the bundles in the bundles folder, sorted.
+pub fn list() -> Vec<String> {
+    let Ok(entries) = bundles_dir().and_then(|dir| Ok(fs::read_dir(dir)?)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == BUNDLE_EXTENSION))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+impl Bundle {
+    /// Gathers the chosen clickers and macros. Without `hotkeys`, the
+    /// clickers' toggle hotkeys are cleared and the stop hotkey left out.
+    pub fn collect(
+        settings: &Settings,
+        clickers: &[usize],
+        macros: &[String],
+        hotkeys: bool,
+    ) -> Result<Bundle, Box<dyn std::error::Error>> {
+        let clickers = clickers
+            .iter()
+            .filter_map(|&index| settings.clickers.get(index).cloned())
+            .map(|mut clicker| {
+                if !hotkeys {
+                    clicker.hotkey = Hotkey::default();
+                }
+                clicker
+            })
+            .collect();
+        let macros = macros
+            .iter()
+            .map(|name| Ok((name.clone(), fs::read_to_string(Macro::path(name)?)?)))
+            .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+        Ok(Bundle {
+            version: BUNDLE_VERSION,
+            clickers,
+            macros,
+            script_stop_hotkey: hotkeys.then(|| settings.scripts.stop_hotkey.clone()),
+        })
+    }
+
+    /// Writes the bundle to the bundles folder and returns the path.
+    pub fn save(&self, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let dir = bundles_dir()?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(name).with_extension(BUNDLE_EXTENSION);
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Reads a bundle given by name (from the bundles folder) or by path.
+    pub fn load(name: &str) -> Result<Bundle, Box<dyn std::error::Error>> {
+        let path = Path::new(name);
+        let path = if path.is_file() {
+            path.to_path_buf()
+        } else {
+            bundles_dir()?.join(name).with_extension(BUNDLE_EXTENSION)
+        };
+        let bundle: Bundle = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if bundle.version > BUNDLE_VERSION {
+            return Err(format!("bundle version {} is newer than this app supports", bundle.version).into());
+        }
+        Ok(bundle)
+    }
+
+    /// Works out how the bundle merges into `settings`, given the names of
+    /// the macros already there. With `replace`, items with a name that is
+    /// already taken replace the local one; otherwise they are renamed.
+    pub fn plan(&self, settings: &Settings, local_macros: &[String], replace: bool) -> ImportPlan {
+        let mut taken: Vec<String> = settings.clickers.iter().map(|clicker| clicker.name.clone()).collect();
+        let mut clickers = Vec::new();
+        for clicker in &self.clickers {
+            let mut clicker = clicker.clone();
+            let status = if !taken.contains(&clicker.name) {
+                ItemStatus::New
+            } else if replace {
+                ItemStatus::Replaces
+            } else {
+                let original = clicker.name.clone();
+                clicker.name = free_name(&original, &taken);
+                ItemStatus::Renamed(original)
+            };
+            taken.push(clicker.name.clone());
+            clickers.push((clicker, status));
+        }
+
+        let mut macros = Vec::new();
+        let mut invalid = Vec::new();
+        let mut taken_macros = local_macros.to_vec();
+        for (name, text) in &self.macros {
+            // Names become file names; anything that would leave the macros
+            // folder or clash with a reserved name is refused outright.
+            if macros::file_stem(name) != *name {
+                invalid.push(format!("{} (not a safe file name)", name));
+                continue;
+            }
+            if let Err(error) = Macro::parse(text) {
+                invalid.push(format!("{} ({})", name, error));
+                continue;
+            }
+            let (target, status) = if !taken_macros.contains(name) {
+                (name.clone(), ItemStatus::New)
+            } else if replace {
+                (name.clone(), ItemStatus::Replaces)
+            } else {
+                (free_name(name, &taken_macros), ItemStatus::Renamed(name.clone()))
+            };
+            taken_macros.push(target.clone());
+            macros.push((target, text.clone(), status));
+        }
+
+        let mut plan = ImportPlan {
+            clickers,
+            macros,
+            script_stop_hotkey: self.script_stop_hotkey.clone(),
+            conflicts: Vec::new(),
+            invalid,
+        };
+        plan.conflicts = plan.find_conflicts(settings);
+        plan
+    }
+}
+
+/// `name (2)`, `name (3)`, ... whichever is free first.
+fn free_name(name: &str, taken: &[String]) -> String {
+    (2..)
+        .map(|number| format!("{} ({})", name, number))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// What happens to one imported item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemStatus {
+    New,
+    /// Imported under a new name; holds the name in the bundle.
+    Renamed(String),
+    /// Replaces the local item of the same name.
+    Replaces,
+}
+
+/// A previewed import. Clicker hotkeys that clash with another binding are
+/// cleared on import and listed in `conflicts`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportPlan {
+    pub clickers: Vec<(ClickerConfig, ItemStatus)>,
+    /// Target name, text and status of each macro.
+    pub macros: Vec<(String, String, ItemStatus)>,
+    pub script_stop_hotkey: Option<Hotkey>,
+    /// Imported clickers whose hotkey will be cleared, with what it clashes with.
+    pub conflicts: Vec<(String, String)>,
+    /// Macros that don't parse or have an unsafe name, and are skipped.
+    pub invalid: Vec<String>,
+}
+
+/// True if both press the same key with the same modifiers.
+fn same_binding(a: &Hotkey, b: &Hotkey) -> bool {
+    let key = |hotkey: &Hotkey| hotkey.key.as_deref().and_then(keys::parse_key);
+    match (key(a), key(b)) {
+        (Some(key_a), Some(key_b)) => key_a == key_b && (a.ctrl, a.alt, a.shift) == (b.ctrl, b.alt, b.shift),
+        _ => false,
+    }
+}
+
+impl ImportPlan {
+    /// Clickers whose hotkey clashes with the stop hotkey, a local clicker
+    /// that stays, or an imported clicker earlier in the list.
+    fn find_conflicts(&self, settings: &Settings) -> Vec<(String, String)> {
+        let stop_hotkey = self.script_stop_hotkey.as_ref().unwrap_or(&settings.scripts.stop_hotkey);
+        let mut bound: Vec<(String, Hotkey)> = vec![(String::from("the script stop hotkey"), stop_hotkey.clone())];
+        let replaced: Vec<&str> = self
+            .clickers
+            .iter()
+            .filter(|(_, status)| *status == ItemStatus::Replaces)
+            .map(|(clicker, _)| clicker.name.as_str())
+            .collect();
+        bound.extend(
+            settings
+                .clickers
+                .iter()
+                .filter(|clicker| !replaced.contains(&clicker.name.as_str()))
+                .map(|clicker| (clicker.name.clone(), clicker.hotkey.clone())),
+        );
+
+        let mut conflicts = Vec::new();
+        for (clicker, _) in &self.clickers {
+            match bound.iter().find(|(_, hotkey)| same_binding(hotkey, &clicker.hotkey)) {
+                Some((owner, _)) => conflicts.push((clicker.name.clone(), owner.clone())),
+                None => bound.push((clicker.name.clone(), clicker.hotkey.clone())),
+            }
+        }
+        conflicts
+    }
+
+    /// Merges the clickers and stop hotkey into `settings`.
+    pub fn apply_to(&self, settings: &mut Settings) {
+        for (clicker, status) in &self.clickers {
+            let mut clicker = clicker.clone();
+            if self.conflicts.iter().any(|(name, _)| *name == clicker.name) {
+                clicker.hotkey = Hotkey::default();
+            }
+            match settings.clickers.iter_mut().find(|local| local.name == clicker.name) {
+                // Schedules keep pointing at a replaced clicker.
+                Some(local) if *status == ItemStatus::Replaces => *local = ClickerConfig { id: local.id, ..clicker },
+                _ => settings.clickers.push(ClickerConfig { id: 0, ..clicker }),
+            }
+        }
+        settings.assign_clicker_ids();
+        if let Some(hotkey) = &self.script_stop_hotkey {
+            settings.scripts.stop_hotkey = hotkey.clone();
+        }
+    }
+
+    /// `settings` with the bundle merged in, or why the result isn't valid.
+    pub fn merged(&self, settings: &Settings) -> Result<Settings, Box<dyn std::error::Error>> {
+        let mut merged = settings.clone();
+        self.apply_to(&mut merged);
+        merged.validate()?;
+        Ok(merged)
+    }
+
+    /// Writes the macros to the macros folder.
+    pub fn write_macros(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for (name, text, _) in &self.macros {
+            let path = Macro::path(name)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, text)?;
+        }
+        Ok(())
+    }
+}
+
+/// One line per item, for the import preview.
+impl fmt::Display for ImportPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = |status: &ItemStatus| match status {
+            ItemStatus::New => String::from("new"),
+            ItemStatus::Renamed(original) => format!("renamed from {}", original),
+            ItemStatus::Replaces => String::from("replaces local"),
+        };
+        for (clicker, item) in &self.clickers {
+            writeln!(f, "Clicker {}: {}", clicker.name, status(item))?;
+        }
+        for (name, _, item) in &self.macros {
+            writeln!(f, "Macro {}: {}", name, status(item))?;
+        }
+        if let Some(hotkey) = &self.script_stop_hotkey {
+            writeln!(f, "Script stop hotkey: {}", hotkey)?;
+        }
+        for (clicker, owner) in &self.conflicts {
+            writeln!(f, "Hotkey of {} clashes with {} and will be cleared", clicker, owner)?;
+        }
+        for invalid in &self.invalid {
+            writeln!(f, "Skipped invalid macro {}", invalid)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clicker(name: &str, key: Option<&str>) -> ClickerConfig {
+        ClickerConfig {
+            name: name.to_string(),
+            hotkey: Hotkey {
+                ctrl: true,
+                key: key.map(String::from),
+                ..Hotkey::default()
+            },
+            ..ClickerConfig::default()
+        }
+    }
+
+    fn local() -> Settings {
+        Settings {
+            clickers: vec![clicker("Farm", Some("F6")), clicker("Fish", Some("F7"))],
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn name_collisions_are_renamed_or_replaced() {
+        let bundle = Bundle {
+            version: BUNDLE_VERSION,
+            clickers: vec![clicker("Farm", None), clicker("Mine", None)],
+            macros: BTreeMap::from([(String::from("combo"), String::from("click left\n"))]),
+            script_stop_hotkey: None,
+        };
+
+        let plan = bundle.plan(&local(), &[String::from("combo")], false);
+        assert_eq!(plan.clickers[0].0.name, "Farm (2)");
+        assert_eq!(plan.clickers[0].1, ItemStatus::Renamed(String::from("Farm")));
+        assert_eq!(plan.clickers[1].1, ItemStatus::New);
+        assert_eq!(plan.macros[0].0, "combo (2)");
+
+        let mut settings = local();
+        let plan = bundle.plan(&settings, &[], true);
+        assert_eq!(plan.clickers[0].1, ItemStatus::Replaces);
+        plan.apply_to(&mut settings);
+        let names: Vec<&str> = settings.clickers.iter().map(|clicker| clicker.name.as_str()).collect();
+        assert_eq!(names, ["Farm", "Fish", "Mine"]);
+        assert_eq!(settings.clickers[0].hotkey.key, None);
+    }
+
+    #[test]
+    fn clashing_hotkeys_are_cleared() {
+        let bundle = Bundle {
+            version: BUNDLE_VERSION,
+            clickers: vec![clicker("Mine", Some("f7")), clicker("Dig", Some("F9")), clicker("Chop", Some("F9"))],
+            ..Bundle::default()
+        };
+        let mut settings = local();
+        let plan = bundle.plan(&settings, &[], false);
+        assert_eq!(
+            plan.conflicts,
+            vec![
+                (String::from("Mine"), String::from("Fish")),
+                (String::from("Chop"), String::from("Dig")),
+            ]
+        );
+
+        plan.apply_to(&mut settings);
+        let keys: Vec<Option<&str>> = settings.clickers.iter().map(|clicker| clicker.hotkey.key.as_deref()).collect();
+        assert_eq!(keys, [Some("F6"), Some("F7"), None, Some("F9"), None]);
+    }
+
+    #[test]
+    fn invalid_macros_are_skipped_and_bundles_round_trip() {
+        let bundle = Bundle {
+            version: BUNDLE_VERSION,
+            clickers: vec![clicker("Mine", None)],
+            macros: BTreeMap::from([(String::from("broken"), String::from("jump\n"))]),
+            script_stop_hotkey: Some(Hotkey::default()),
+        };
+        let plan = bundle.plan(&local(), &[], false);
+        assert!(plan.macros.is_empty());
+        assert_eq!(plan.invalid.len(), 1);
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(serde_json::from_str::<Bundle>(&json).unwrap(), bundle);
+    }
+
+    #[test]
+    fn unsafe_macro_names_and_invalid_clickers_are_refused() {
+        let text = String::from("click left\n");
+        let bundle = Bundle {
+            version: BUNDLE_VERSION,
+            clickers: vec![ClickerConfig { interval_ms: 0, ..clicker("Mine", None) }],
+            macros: ["../../x", "/etc/x", "C:\\x", "..", "CON", "fine"]
+                .into_iter()
+                .map(|name| (name.to_string(), text.clone()))
+                .collect(),
+            script_stop_hotkey: None,
+        };
+        let plan = bundle.plan(&local(), &[], false);
+        let names: Vec<&str> = plan.macros.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, ["fine"]);
+        assert_eq!(plan.invalid.len(), 5);
+
+        assert!(plan.merged(&local()).is_err(), "a zero interval should be refused");
+    }
+}
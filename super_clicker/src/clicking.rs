@@ -1,71 +1,669 @@
-use rdev::{simulate, Button};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use rdev::{simulate, Button, EventType, SimulateError};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
+use tracing::{debug, warn};
 
+use super::keys;
+use super::motion;
+use super::settings::{Action, ClickerConfig, Motion, MouseButton, PatternStep, ScrollDirection};
+
+/// How long a button or key is held down for each click.
+pub const PRESS_HOLD: Duration = Duration::from_millis(10);
+
+/// Time between cursor moves while gliding or following a path.
+const MOVE_STEP: Duration = Duration::from_millis(10);
+
+/// Measured sleep overshoot, in microseconds. Engines wake this much early
+/// (see `set_timing_offset`).
+static TIMING_OFFSET_US: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the per-machine calibration offset (`Settings::timing_offset_us`),
+/// found with the `benchmark` module.
+pub fn set_timing_offset(offset: Duration) {
+    TIMING_OFFSET_US.store(offset.as_micros() as u64, Ordering::Relaxed);
+}
+
+pub fn timing_offset() -> Duration {
+    Duration::from_micros(TIMING_OFFSET_US.load(Ordering::Relaxed))
+}
+
+/// Hard cap on actions per second for all engines and scripts together,
+/// whatever set their intervals. 0 disables the cap.
+static MAX_CPS: AtomicU32 = AtomicU32::new(100);
+
+/// Sets the cap from `Settings::rate_limit`.
+pub fn set_max_cps(max_cps: u32) {
+    MAX_CPS.store(max_cps, Ordering::Relaxed);
+}
+
+/// Shortest time allowed between two actions, if capped.
+pub fn min_action_gap() -> Option<Duration> {
+    let max_cps = MAX_CPS.load(Ordering::Relaxed);
+    (max_cps > 0).then(|| Duration::from_secs(1) / max_cps)
+}
+
+/// Hands out time slots for actions at least a gap apart.
+#[derive(Debug, Default)]
+struct RateLimiter {
+    last: Option<Instant>,
+}
+
+impl RateLimiter {
+    const fn new() -> Self {
+        RateLimiter { last: None }
+    }
+
+    /// Books the earliest slot `gap` after the last one booked, or now.
+    fn reserve(&mut self, gap: Option<Duration>) -> Instant {
+        let now = Instant::now();
+        let slot = match (gap, self.last) {
+            (Some(gap), Some(last)) => now.max(last + gap),
+            _ => now,
+        };
+        self.last = Some(slot);
+        slot
+    }
+}
+
+/// One limiter for the whole process, so the cap holds across engines and scripts.
+static LIMITER: Mutex<RateLimiter> = Mutex::new(RateLimiter::new());
+
+/// Sleeps until the cap allows another action, then counts it as sent.
+/// Slots are booked under the lock but slept outside it.
+pub fn throttle() {
+    let slot = LIMITER.lock().map(|mut limiter| limiter.reserve(min_action_gap()));
+    if let Ok(slot) = slot {
+        thread::sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// How long a sent event waits for the listener to see it come back.
+const ECHO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Most sent events kept waiting for their echo.
+const ECHO_CAPACITY: usize = 64;
+
+/// Events we sent that the listener hasn't seen yet, oldest first.
+#[derive(Debug, Default)]
+struct EchoQueue {
+    sent: VecDeque<(Instant, EventType)>,
+}
+
+impl EchoQueue {
+    const fn new() -> Self {
+        EchoQueue { sent: VecDeque::new() }
+    }
+
+    fn record(&mut self, event: EventType, now: Instant) {
+        if self.sent.len() == ECHO_CAPACITY {
+            self.sent.pop_front();
+        }
+        self.sent.push_back((now, event));
+    }
+
+    /// Removes the sent event `event` echoes, if any. Each echo is matched once.
+    fn take(&mut self, event: &EventType, now: Instant) -> bool {
+        self.sent.retain(|(sent, _)| now.duration_since(*sent) <= ECHO_TIMEOUT);
+        match self.sent.iter().position(|(_, sent)| is_echo(sent, event)) {
+            Some(index) => {
+                self.sent.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// True if `seen` is `sent` coming back. Cursor positions may come back
+/// rounded, so they only need to be within a pixel.
+fn is_echo(sent: &EventType, seen: &EventType) -> bool {
+    match (sent, seen) {
+        (EventType::MouseMove { x, y }, EventType::MouseMove { x: seen_x, y: seen_y }) => {
+            (x - seen_x).abs() < 1.0 && (y - seen_y).abs() < 1.0
+        }
+        _ => sent == seen,
+    }
+}
+
+/// What `RdevBackend` sent, for `is_own_input`.
+static SENT: Mutex<EchoQueue> = Mutex::new(EchoQueue::new());
+
+/// True if a listened event is our own simulated input: exactly what we sent
+/// moments ago. Used to tell the user's activity apart, even mid-glide.
+pub fn is_own_input(event: &EventType) -> bool {
+    SENT.lock().is_ok_and(|mut sent| sent.take(event, Instant::now()))
+}
+
+/// Last known cursor position on screen, from the global listener or our own moves.
+static CURSOR: Mutex<Option<(f64, f64)>> = Mutex::new(None);
+
+/// Records where the cursor is; called by the global listener on every move.
+pub fn note_cursor(x: f64, y: f64) {
+    if let Ok(mut cursor) = CURSOR.lock() {
+        *cursor = Some((x, y));
+    }
+}
+
+/// Where simulated input goes.
+pub trait InputBackend: Send + Sync {
+    fn simulate(&self, event: &EventType) -> Result<(), SimulateError>;
+
+    /// Where the cursor is, if known. Jiggles and glides need a starting point.
+    fn cursor_position(&self) -> Option<(f64, f64)>;
+}
+
+/// Sends input to the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RdevBackend;
+
+impl InputBackend for RdevBackend {
+    fn simulate(&self, event: &EventType) -> Result<(), SimulateError> {
+        if let Ok(mut sent) = SENT.lock() {
+            sent.record(*event, Instant::now());
+        }
+        if let EventType::MouseMove { x, y } = *event {
+            note_cursor(x, y);
+        }
+        simulate(event)
+    }
+
+    fn cursor_position(&self) -> Option<(f64, f64)> {
+        CURSOR.lock().ok().and_then(|cursor| *cursor)
+    }
+}
+
+/// Records input with the time it was sent, for tests and benchmarks.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    events: Mutex<Vec<(Instant, EventType)>>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything sent so far, oldest first.
+    pub fn events(&self) -> Vec<(Instant, EventType)> {
+        self.events.lock().map(|events| events.clone()).unwrap_or_default()
+    }
+
+    /// Every cursor position sent, in order.
+    pub fn moves(&self) -> Vec<(f64, f64)> {
+        self.events()
+            .into_iter()
+            .filter_map(|(_, event)| match event {
+                EventType::MouseMove { x, y } => Some((x, y)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// When each button press, key press or scroll was sent.
+    pub fn press_times(&self) -> Vec<Instant> {
+        self.events()
+            .into_iter()
+            .filter(|(_, event)| {
+                matches!(event, EventType::ButtonPress(_) | EventType::KeyPress(_) | EventType::Wheel { .. })
+            })
+            .map(|(time, _)| time)
+            .collect()
+    }
+}
+
+impl InputBackend for MockBackend {
+    fn simulate(&self, event: &EventType) -> Result<(), SimulateError> {
+        if let Ok(mut events) = self.events.lock() {
+            events.push((Instant::now(), *event));
+        }
+        Ok(())
+    }
+
+    /// The last position moved to; unknown before the first move.
+    fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.moves().last().copied()
+    }
+}
+
+/// A step as the engine thread runs it.
+enum Step {
+    Act(Action),
+    MoveTo(i32, i32),
+    Wait(Duration),
+    /// Wait for the clicker's interval, which can change while running.
+    WaitInterval,
+}
+
+/// The loop a clicker runs: its pattern, or its action followed by the interval.
+fn steps(config: &ClickerConfig) -> Vec<Step> {
+    // A pattern of only waits would spin without doing anything.
+    if !config.pattern.iter().any(|step| matches!(step, PatternStep::Act(_))) {
+        return vec![Step::Act(config.action.clone()), Step::WaitInterval];
+    }
+    config
+        .pattern
+        .iter()
+        .map(|step| match step {
+            PatternStep::Act(action) => Step::Act(action.clone()),
+            PatternStep::MoveTo(x, y) => Step::MoveTo(*x, *y),
+            PatternStep::Wait(ms) => Step::Wait(Duration::from_millis(*ms)),
+        })
+        .collect()
+}
+
+/// The controls the app needs from a clicker's engine, so tests can swap
+/// in one that records calls instead of sending input.
+pub trait ClickerEngine {
+    fn start(&self, config: &ClickerConfig);
+    fn stop(&self);
+    fn update_interval(&self, new_ms: u64);
+    fn click_count(&self) -> u64;
+}
+
+/// Runs one clicker on a background thread. Cheap to create; `start` and
+/// `stop` may be called any number of times.
 pub struct ClickingEngine {
     is_running: Arc<AtomicBool>,
     interval: Arc<AtomicU64>,
+    click_count: Arc<AtomicU64>,
+    // Bumped on every start so a thread from a previous run can tell it is stale.
+    generation: Arc<AtomicU64>,
+    backend: Arc<dyn InputBackend>,
+    /// Whether actions go through the process-wide CPS cap.
+    capped: bool,
 }
 
 impl ClickingEngine {
     pub fn new() -> Self {
+        Self::with_backend(Arc::new(RdevBackend))
+    }
+
+    /// An engine that sends its input to `backend` instead of the OS.
+    pub fn with_backend(backend: Arc<dyn InputBackend>) -> Self {
         ClickingEngine {
             is_running: Arc::new(AtomicBool::new(false)),
             interval: Arc::new(AtomicU64::new(100)),
+            click_count: Arc::new(AtomicU64::new(0)),
+            generation: Arc::new(AtomicU64::new(0)),
+            backend,
+            capped: true,
         }
     }
 
+    /// Leaves this engine out of the CPS cap. For engines that don't reach
+    /// the OS, like the benchmark's.
+    pub fn uncapped(mut self) -> Self {
+        self.capped = false;
+        self
+    }
+
+    /// Changes the interval of a running plain (non-pattern) clicker.
     pub fn update_interval(&self, new_ms: u64) {
         self.interval.store(new_ms, Ordering::Relaxed);
     }
 
-    pub fn start(&self, button: &str, interval_ms: u64) {
+    /// Number of clicks sent since the last `start`.
+    pub fn click_count(&self) -> u64 {
+        self.click_count.load(Ordering::Relaxed)
+    }
+
+    /// Starts a clicking thread for `config`. Each engine owns its thread, so
+    /// several engines run side by side without affecting each other's timing.
+    pub fn start(&self, config: &ClickerConfig) {
         let is_running = Arc::clone(&self.is_running);
         let interval = Arc::clone(&self.interval);
-        let button_clone = button.to_string();
+        let click_count = Arc::clone(&self.click_count);
+        let generation = Arc::clone(&self.generation);
+        let backend = Arc::clone(&self.backend);
+        let capped = self.capped;
+        let steps = steps(config);
+        let target = config.target;
+        let area = config.area;
+        let motion = config.motion.clone();
+        let glide = Duration::from_millis(config.glide_ms);
+        debug!(clicker = %config.name, interval_ms = config.interval_ms, steps = steps.len(), "engine started");
+
+        click_count.store(0, Ordering::Relaxed);
+        let run = generation.fetch_add(1, Ordering::Relaxed) + 1;
 
         // Set interval
-        interval.store(interval_ms, Ordering::Relaxed);
+        interval.store(config.interval_ms, Ordering::Relaxed);
         
         // Set running state to true
         is_running.store(true, Ordering::Relaxed);
 
         thread::spawn(move || {
-            loop {
-                let start_time = Instant::now();
+            // Waits are measured from here, so the time spent pressing
+            // doesn't add up over the pattern.
+            let mut anchor = Instant::now();
+            // Paths are timed from the start, independent of the actions.
+            let started = anchor;
+            let mut jiggles = 0;
+            let mut rng = rand::thread_rng();
 
-                if !is_running.load(Ordering::Relaxed) {
+            let stopped = || !is_running.load(Ordering::Relaxed) || generation.load(Ordering::Relaxed) != run;
+
+            for step in steps.iter().cycle() {
+                if stopped() {
                     break;
                 }
 
-                let btn = match button_clone.as_str() {
-                    "Left" => Button::Left,
-                    "Right" => Button::Right,
-                    "Middle" => Button::Middle,
-                    _ => Button::Left,
+                let wait = match step {
+                    Step::Act(action) => {
+                        if capped {
+                            throttle();
+                            // Stop All or the fail-safe may have come while waiting for a slot.
+                            if stopped() {
+                                break;
+                            }
+                        }
+                        anchor = Instant::now();
+                        let point = area.map(|area| motion::area_point(&area, &mut rng)).or(target);
+                        if let Some((x, y)) = point {
+                            glide_to(backend.as_ref(), x, y, glide, &stopped);
+                            if stopped() {
+                                break;
+                            }
+                        }
+                        perform(backend.as_ref(), action);
+                        click_count.fetch_add(1, Ordering::Relaxed);
+                        if let Motion::Jiggle { pixels } = motion {
+                            jiggle(backend.as_ref(), jiggles, pixels);
+                            jiggles += 1;
+                        }
+                        continue;
+                    }
+                    Step::MoveTo(x, y) => {
+                        glide_to(backend.as_ref(), *x, *y, glide, &stopped);
+                        continue;
+                    }
+                    Step::Wait(wait) => *wait,
+                    // Read current interval dynamically
+                    Step::WaitInterval => Duration::from_millis(interval.load(Ordering::Relaxed)),
                 };
 
-                // Simulate mouse down and up for a click
-                let _ = simulate(&rdev::EventType::ButtonPress(btn));
-                thread::sleep(Duration::from_millis(10));
-                let _ = simulate(&rdev::EventType::ButtonRelease(btn));
-
-                // Read current interval dynamically
-                let current_interval = interval.load(Ordering::Relaxed);
-
-                // Wait until the total interval_ms has elapsed since the start of this click
-                let elapsed = start_time.elapsed().as_millis() as u64;
-                if elapsed < current_interval {
-                    let wait_ms = current_interval - elapsed;
-                    thread::sleep(Duration::from_millis(wait_ms));
+                anchor += wait;
+                let now = Instant::now();
+                if anchor > now {
+                    // Wake early by the calibrated overshoot so the action lands on time.
+                    let early = timing_offset().min(anchor - now);
+                    pause(backend.as_ref(), &motion, started, anchor - now - early);
+                } else {
+                    // Running late (e.g. a long key hold); don't try to catch up.
+                    anchor = now;
                 }
             }
+            debug!(clicks = click_count.load(Ordering::Relaxed), "engine thread exited");
         });
     }
 
+    /// Asks the clicking thread to exit after its current step.
     pub fn stop(&self) {
         self.is_running.store(false, Ordering::Relaxed);
     }
-}
\ No newline at end of file
+}
+
+impl ClickerEngine for ClickingEngine {
+    fn start(&self, config: &ClickerConfig) {
+        ClickingEngine::start(self, config);
+    }
+
+    fn stop(&self) {
+        ClickingEngine::stop(self);
+    }
+
+    fn update_interval(&self, new_ms: u64) {
+        ClickingEngine::update_interval(self, new_ms);
+    }
+
+    fn click_count(&self) -> u64 {
+        ClickingEngine::click_count(self)
+    }
+}
+
+impl Default for ClickingEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Moves the cursor to a screen position.
+pub fn move_to(backend: &dyn InputBackend, x: i32, y: i32) {
+    if let Err(error) = backend.simulate(&EventType::MouseMove { x: x as f64, y: y as f64 }) {
+        warn!(?error, x, y, "could not move the cursor");
+    }
+}
+
+/// Moves the cursor to a screen position along a curve over `duration`. Jumps
+/// there if the duration is zero or the cursor position is unknown. Gives up
+/// between steps once `stopped` returns true.
+pub fn glide_to(backend: &dyn InputBackend, x: i32, y: i32, duration: Duration, stopped: &dyn Fn() -> bool) {
+    let Some(from) = backend.cursor_position().filter(|_| !duration.is_zero()) else {
+        move_to(backend, x, y);
+        return;
+    };
+    let to = (x as f64, y as f64);
+    let moves = (duration.as_millis() / MOVE_STEP.as_millis()).max(1) as u32;
+    for step in 1..=moves {
+        if stopped() {
+            return;
+        }
+        let (x, y) = motion::glide_point(from, to, step as f64 / moves as f64);
+        move_to(backend, x.round() as i32, y.round() as i32);
+        if step < moves {
+            thread::sleep(duration / moves);
+        }
+    }
+}
+
+/// Nudges the cursor for the `index`th jiggle. Does nothing while the cursor
+/// position is unknown.
+fn jiggle(backend: &dyn InputBackend, index: usize, pixels: u32) {
+    if let Some((x, y)) = backend.cursor_position() {
+        let (dx, dy) = motion::jiggle_offset(index, pixels);
+        move_to(backend, x.round() as i32 + dx, y.round() as i32 + dy);
+    }
+}
+
+/// Sleeps for `duration`, moving the cursor along the motion's path meanwhile.
+fn pause(backend: &dyn InputBackend, motion: &Motion, started: Instant, duration: Duration) {
+    if motion::path_point(motion, Duration::ZERO).is_none() {
+        thread::sleep(duration);
+        return;
+    }
+    let until = Instant::now() + duration;
+    loop {
+        let now = Instant::now();
+        if let Some((x, y)) = motion::path_point(motion, now - started) {
+            move_to(backend, x.round() as i32, y.round() as i32);
+        }
+        if now >= until {
+            return;
+        }
+        thread::sleep((until - now).min(MOVE_STEP));
+    }
+}
+
+/// Simulates one press-and-release of `action`, or one turn of the wheel.
+pub fn perform(backend: &dyn InputBackend, action: &Action) {
+    let (press, release) = match action {
+        Action::Scroll(direction, notches) => {
+            let notches = i64::from(*notches);
+            // rdev counts in notches: positive is up and right.
+            let (delta_x, delta_y) = match direction {
+                ScrollDirection::Up => (0, notches),
+                ScrollDirection::Down => (0, -notches),
+                ScrollDirection::Left => (-notches, 0),
+                ScrollDirection::Right => (notches, 0),
+            };
+            if let Err(error) = backend.simulate(&EventType::Wheel { delta_x, delta_y }) {
+                warn!(?error, "could not scroll");
+            }
+            return;
+        }
+        Action::Click(button) => {
+            let btn = match button {
+                MouseButton::Left => Button::Left,
+                MouseButton::Middle => Button::Middle,
+                MouseButton::Right => Button::Right,
+            };
+            (EventType::ButtonPress(btn), EventType::ButtonRelease(btn))
+        }
+        Action::Press(name) => match keys::parse_key(name) {
+            Some(key) => (EventType::KeyPress(key), EventType::KeyRelease(key)),
+            None => {
+                warn!(key = %name, "unknown key, nothing sent");
+                return;
+            }
+        },
+    };
+
+    if let Err(error) = backend.simulate(&press) {
+        warn!(?error, "could not send input");
+    }
+    thread::sleep(PRESS_HOLD);
+    let _ = backend.simulate(&release);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{AreaShape, ClickArea, Spread};
+    use rdev::Key;
+
+    #[test]
+    fn rate_limiter_spaces_actions() {
+        let mut limiter = RateLimiter::new();
+        let gap = Some(Duration::from_millis(30));
+        let slots: Vec<Instant> = (0..3).map(|_| limiter.reserve(gap)).collect();
+        // The first action goes straight out; the next two wait a gap each,
+        // however quickly they were booked.
+        assert_eq!(slots[2] - slots[0], Duration::from_millis(60));
+
+        let mut unlimited = RateLimiter::new();
+        let slots: Vec<Instant> = (0..3).map(|_| unlimited.reserve(None)).collect();
+        assert!(slots[2] - slots[0] < Duration::from_millis(30));
+    }
+
+    #[test]
+    fn glide_curves_to_the_target() {
+        let backend = MockBackend::new();
+        glide_to(&backend, 10, 10, Duration::from_millis(100), &|| false);
+        assert_eq!(backend.moves(), vec![(10.0, 10.0)], "no start point, so it jumps");
+
+        glide_to(&backend, 210, 10, Duration::from_millis(100), &|| false);
+        let moves = backend.moves();
+        assert_eq!(moves.len(), 11);
+        assert_eq!(moves.last(), Some(&(210.0, 10.0)));
+        assert!(moves.iter().any(|&(_, y)| y != 10.0), "should bend off the straight line");
+        assert!(moves.windows(2).all(|pair| pair[1].0 >= pair[0].0));
+    }
+
+    #[test]
+    fn only_sent_positions_count_as_echoes() {
+        let mut queue = EchoQueue::new();
+        let now = Instant::now();
+        queue.record(EventType::MouseMove { x: 100.0, y: 100.0 }, now);
+        queue.record(EventType::KeyPress(Key::KeyA), now);
+
+        // The user moving the mouse mid-glide isn't ours.
+        assert!(!queue.take(&EventType::MouseMove { x: 140.0, y: 90.0 }, now));
+        assert!(queue.take(&EventType::MouseMove { x: 100.4, y: 99.6 }, now));
+        assert!(!queue.take(&EventType::MouseMove { x: 100.0, y: 100.0 }, now), "already matched");
+        assert!(!queue.take(&EventType::KeyPress(Key::KeyB), now));
+
+        let later = now + ECHO_TIMEOUT + Duration::from_millis(1);
+        assert!(!queue.take(&EventType::KeyPress(Key::KeyA), later), "should have expired");
+    }
+
+    #[test]
+    fn glide_stops_between_steps() {
+        let backend = MockBackend::new();
+        glide_to(&backend, 10, 10, Duration::ZERO, &|| false);
+        let steps = AtomicU32::new(0);
+        glide_to(&backend, 210, 10, Duration::from_millis(100), &|| steps.fetch_add(1, Ordering::Relaxed) >= 2);
+        assert_eq!(backend.moves().len(), 3, "the jump plus two glide steps");
+    }
+
+    #[test]
+    fn engine_jiggles_after_each_click() {
+        let backend = Arc::new(MockBackend::new());
+        let engine = ClickingEngine::with_backend(backend.clone()).uncapped();
+        engine.start(&ClickerConfig {
+            interval_ms: 20,
+            target: Some((100, 100)),
+            motion: Motion::Jiggle { pixels: 4 },
+            ..ClickerConfig::default()
+        });
+        thread::sleep(Duration::from_millis(100));
+        engine.stop();
+
+        let moves = backend.moves();
+        assert!(moves.len() >= 4, "only {} moves", moves.len());
+        assert_eq!(&moves[..4], &[(100.0, 100.0), (104.0, 100.0), (100.0, 100.0), (100.0, 104.0)]);
+    }
+
+    #[test]
+    fn engine_clicks_inside_the_area() {
+        let backend = Arc::new(MockBackend::new());
+        let engine = ClickingEngine::with_backend(backend.clone()).uncapped();
+        engine.start(&ClickerConfig {
+            interval_ms: 10,
+            target: Some((0, 0)),
+            area: Some(ClickArea {
+                shape: AreaShape::Rectangle,
+                from: (100, 200),
+                to: (120, 230),
+                spread: Spread::Uniform,
+            }),
+            ..ClickerConfig::default()
+        });
+        thread::sleep(Duration::from_millis(100));
+        engine.stop();
+
+        let moves = backend.moves();
+        assert!(moves.len() >= 3, "only {} moves", moves.len());
+        assert!(moves.iter().all(|&(x, y)| (100.0..=120.0).contains(&x) && (200.0..=230.0).contains(&y)));
+        assert!(moves.windows(2).any(|pair| pair[0] != pair[1]), "every click landed on one pixel");
+    }
+
+    #[test]
+    fn engine_follows_a_path_between_clicks() {
+        let backend = Arc::new(MockBackend::new());
+        let engine = ClickingEngine::with_backend(backend.clone()).uncapped();
+        engine.start(&ClickerConfig {
+            interval_ms: 200,
+            motion: Motion::Circle {
+                center: (300, 300),
+                radius: 100,
+                speed: 1000,
+            },
+            ..ClickerConfig::default()
+        });
+        thread::sleep(Duration::from_millis(100));
+        engine.stop();
+
+        let moves = backend.moves();
+        assert!(moves.len() >= 3, "only {} moves", moves.len());
+        assert!(moves.iter().all(|&(x, y)| ((x - 300.0).hypot(y - 300.0) - 100.0).abs() <= 1.0));
+    }
+
+    #[test]
+    fn scroll_sends_one_wheel_event() {
+        let backend = MockBackend::new();
+        perform(&backend, &Action::Scroll(ScrollDirection::Down, 3));
+        perform(&backend, &Action::Scroll(ScrollDirection::Left, 1));
+
+        let events: Vec<EventType> = backend.events().into_iter().map(|(_, event)| event).collect();
+        assert_eq!(
+            events,
+            vec![
+                EventType::Wheel { delta_x: 0, delta_y: -3 },
+                EventType::Wheel { delta_x: -1, delta_y: 0 },
+            ]
+        );
+    }
+}
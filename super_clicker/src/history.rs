@@ -0,0 +1,255 @@
+//! History of clicker sessions.
+//!
+//! Every run that actually started clicking is appended as one JSON line to
+//! `history.jsonl` in the data directory when it stops. The history can be
+//! exported to CSV or JSON for auditing.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use time::{OffsetDateTime, UtcOffset};
+use tracing::warn;
+
+use super::settings::Settings;
+
+/// Why a session ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+    /// The clicker's own Stop button.
+    Manual,
+    /// The clicker's toggle hotkey.
+    Hotkey,
+    StopAll,
+    FailSafe,
+    /// A schedule's run time was over.
+    Schedule,
+    /// The clicker was removed while running.
+    Removed,
+    /// No keyboard or mouse input from the user for `AutoStopSettings::idle_secs`.
+    Idle,
+    /// The computer was locked.
+    Locked,
+    /// `settings.json` was edited and changed the running clicker.
+    Reloaded,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::Manual => write!(f, "stop button"),
+            StopReason::Hotkey => write!(f, "hotkey"),
+            StopReason::StopAll => write!(f, "stop all"),
+            StopReason::FailSafe => write!(f, "fail-safe"),
+            StopReason::Schedule => write!(f, "schedule"),
+            StopReason::Removed => write!(f, "clicker removed"),
+            StopReason::Idle => write!(f, "idle"),
+            StopReason::Locked => write!(f, "computer locked"),
+            StopReason::Reloaded => write!(f, "settings reloaded"),
+        }
+    }
+}
+
+/// One run of a clicker, from start to stop.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub clicker: String,
+    /// The action, or the pattern's length for pattern clickers.
+    pub action: String,
+    pub interval_ms: u64,
+    /// Start time, in seconds since the Unix epoch.
+    pub started: u64,
+    /// Measured on the monotonic clock, so it is exact even if the wall clock jumps.
+    pub duration_ms: u64,
+    /// Actions sent during the run.
+    pub clicks: u64,
+    pub stop_reason: StopReason,
+}
+
+impl Session {
+    /// End time, in seconds since the Unix epoch.
+    pub fn ended(&self) -> u64 {
+        self.started + self.duration_ms / 1000
+    }
+
+    /// Measured actions per second over the whole run.
+    pub fn cps(&self) -> f64 {
+        if self.duration_ms == 0 {
+            0.0
+        } else {
+            self.clicks as f64 * 1000.0 / self.duration_ms as f64
+        }
+    }
+}
+
+/// File format for `export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+pub fn path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(Settings::data_dir()?.join("history.jsonl"))
+}
+
+/// Adds `session` to the end of the history file.
+pub fn append(session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(session)?)?;
+    Ok(())
+}
+
+/// Every recorded session, oldest first. A missing file is an empty history;
+/// damaged lines are skipped.
+pub fn load() -> Result<Vec<Session>, Box<dyn std::error::Error>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(parse_lines(&content))
+}
+
+fn parse_lines(content: &str) -> Vec<Session> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(number, line)| match serde_json::from_str(line) {
+            Ok(session) => Some(session),
+            Err(error) => {
+                warn!(line = number + 1, %error, "skipping damaged history entry");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Writes `sessions` to `history.csv` or `history.json` next to the history
+/// file and returns the path written.
+pub fn export(sessions: &[Session], format: ExportFormat) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let (extension, content) = match format {
+        ExportFormat::Csv => ("csv", to_csv(sessions)),
+        ExportFormat::Json => ("json", serde_json::to_string_pretty(sessions)?),
+    };
+    let path = path()?.with_extension(extension);
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// CSV with a header row. Times are UTC, in RFC 3339.
+pub fn to_csv(sessions: &[Session]) -> String {
+    let mut csv = String::from("clicker,action,interval_ms,started,ended,duration_secs,clicks,cps,stop_reason\n");
+    for session in sessions {
+        let fields = [
+            csv_field(&session.clicker),
+            csv_field(&session.action),
+            session.interval_ms.to_string(),
+            format_utc(session.started),
+            format_utc(session.ended()),
+            format!("{:.3}", session.duration_ms as f64 / 1000.0),
+            session.clicks.to_string(),
+            format!("{:.2}", session.cps()),
+            csv_field(&session.stop_reason.to_string()),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn date_time(secs: u64, offset_secs: i32) -> OffsetDateTime {
+    let offset = UtcOffset::from_whole_seconds(offset_secs).unwrap_or(UtcOffset::UTC);
+    (OffsetDateTime::UNIX_EPOCH + Duration::from_secs(secs)).to_offset(offset)
+}
+
+/// "2026-10-19T08:30:00Z"
+pub fn format_utc(secs: u64) -> String {
+    let time = date_time(secs, 0);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// "2026-10-19 10:30", shifted by `offset_secs` from UTC.
+pub fn format_local(secs: u64, offset_secs: i32) -> String {
+    let time = date_time(secs, offset_secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session {
+        Session {
+            clicker: String::from("Farm, left"),
+            action: String::from("Left Click"),
+            interval_ms: 100,
+            started: 1_760_862_600, // 2025-10-19T08:30:00Z
+            duration_ms: 125_500,
+            clicks: 1255,
+            stop_reason: StopReason::Hotkey,
+        }
+    }
+
+    #[test]
+    fn measures_cps_and_end_time() {
+        let session = session();
+        assert_eq!(session.ended(), 1_760_862_725);
+        assert!((session.cps() - 10.0).abs() < 1e-9);
+        assert_eq!(Session { duration_ms: 0, ..session }.cps(), 0.0);
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_uses_utc_times() {
+        let csv = to_csv(&[session()]);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("clicker,action,interval_ms,started,ended,duration_secs,clicks,cps,stop_reason")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("\"Farm, left\",Left Click,100,2025-10-19T08:30:00Z,2025-10-19T08:32:05Z,125.500,1255,10.00,hotkey")
+        );
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn history_lines_round_trip_and_skip_damage() {
+        let line = serde_json::to_string(&session()).unwrap();
+        let content = format!("{}\nnot json\n\n{}\n", line, line);
+        assert_eq!(parse_lines(&content), vec![session(), session()]);
+        assert_eq!(format_local(session().started, 2 * 3600), "2025-10-19 10:30");
+    }
+}
@@ -1,98 +1,379 @@
-use iced::futures::channel::mpsc;
-use iced::futures::{SinkExt, StreamExt};
-use iced::Subscription;
-use iced::keyboard;
-use iced::mouse;
-use iced::Event;
-use iced::event;
-use rdev::{listen, EventType, Key};
+//! Global hotkeys and fail-safe triggers, read from the raw input stream.
+//!
+//! The bindings are process-wide: update them with `set_toggle_hotkeys`,
+//! `set_script_stop_hotkey`, `set_fail_safe`, `set_auto_stop` and
+//! `set_capturing`, and run `run_listener` and `run_auto_stop_watcher` on
+//! threads of their own to receive `HotkeyEvent`s.
+
+use rdev::{listen, Button, EventType, Key};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, warn};
+
+use super::clicking::{self, InputBackend, RdevBackend};
+use super::history::StopReason;
+use super::keys;
+use super::settings::{AutoStopSettings, FailSafeSettings, Hotkey};
+
+/// Fail-safe configuration read by the listener thread on every event.
+static FAIL_SAFE: LazyLock<Mutex<FailSafeSettings>> =
+    LazyLock::new(|| Mutex::new(FailSafeSettings::default()));
+
+/// Toggle hotkeys of every clicker, indexed like `Settings::clickers`.
+static TOGGLE_BINDINGS: LazyLock<Mutex<Vec<Option<ToggleBinding>>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// Hotkey that stops the running script.
+static SCRIPT_STOP_BINDING: LazyLock<Mutex<Option<ToggleBinding>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+/// Idle and lock auto-stop configuration, read by the watcher thread.
+static AUTO_STOP: LazyLock<Mutex<AutoStopSettings>> =
+    LazyLock::new(|| Mutex::new(AutoStopSettings::default()));
+
+/// Last keyboard or mouse input from the user (not our own simulated input).
+static LAST_USER_INPUT: LazyLock<Mutex<Instant>> = LazyLock::new(|| Mutex::new(Instant::now()));
+
+/// While set, left clicks by the user are reported as `HotkeyEvent::PointCaptured`.
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// How often the watcher checks for idle time and a locked computer.
+const AUTO_STOP_POLL: Duration = Duration::from_secs(1);
+
+/// A parsed `Hotkey`, ready to compare against raw key presses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ToggleBinding {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    key: Key,
+}
+
+impl ToggleBinding {
+    fn parse(hotkey: &Hotkey) -> Option<Self> {
+        let key = keys::parse_key(hotkey.key.as_deref()?)?;
+        Some(ToggleBinding {
+            ctrl: hotkey.ctrl,
+            alt: hotkey.alt,
+            shift: hotkey.shift,
+            key,
+        })
+    }
+}
+
+/// Replaces the clicker toggle hotkeys. `HotkeyEvent::Toggle(i)` refers to
+/// the `i`-th entry. Unset or unknown keys are never matched.
+pub fn set_toggle_hotkeys(hotkeys: &[Hotkey]) {
+    if let Ok(mut bindings) = TOGGLE_BINDINGS.lock() {
+        *bindings = hotkeys.iter().map(ToggleBinding::parse).collect();
+    }
+}
+
+/// Starts or stops reporting the user's left clicks, e.g. to pick the
+/// corners of a click area.
+pub fn set_capturing(capturing: bool) {
+    CAPTURING.store(capturing, Ordering::Relaxed);
+}
+
+/// Replaces the hotkey that sends `HotkeyEvent::StopScript`.
+pub fn set_script_stop_hotkey(hotkey: &Hotkey) {
+    if let Ok(mut binding) = SCRIPT_STOP_BINDING.lock() {
+        *binding = ToggleBinding::parse(hotkey);
+    }
+}
+
+/// Maximum pause between Escape presses that still counts as "in a row".
+const ESCAPE_SEQUENCE_GAP: Duration = Duration::from_secs(1);
+
+/// Replaces the fail-safe configuration used by the global listener.
+pub fn set_fail_safe(settings: FailSafeSettings) {
+    if let Ok(mut fail_safe) = FAIL_SAFE.lock() {
+        *fail_safe = settings;
+    }
+}
+
+/// Replaces the idle and lock auto-stop configuration.
+pub fn set_auto_stop(settings: AutoStopSettings) {
+    if let Ok(mut auto_stop) = AUTO_STOP.lock() {
+        *auto_stop = settings;
+    }
+}
+
+/// Time since the user last touched the keyboard or mouse, as seen by `run_listener`.
+pub fn idle_for() -> Duration {
+    LAST_USER_INPUT.lock().map(|last| last.elapsed()).unwrap_or_default()
+}
+
+/// What tripped the emergency stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailSafeTrigger {
+    ScreenCorner,
+    MouseMovement,
+    EscapeRepeated,
+}
+
+impl fmt::Display for FailSafeTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailSafeTrigger::ScreenCorner => write!(f, "cursor in corner"),
+            FailSafeTrigger::MouseMovement => write!(f, "mouse moved"),
+            FailSafeTrigger::EscapeRepeated => write!(f, "Escape pressed"),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum HotkeyEvent {
-    Toggle,
-    IntervalChange(i32), // From rdev (Trusted)
+    Toggle(usize), // Index of the clicker whose hotkey was pressed
+    ToggleMiniMode,
+    IntervalChange(i32), // From rdev (Trusted): wheel delta or +/-1 from arrow keys
     LocalScroll(f32),    // From iced (Untrusted, need to check modifiers)
     ModifiersChanged(bool, bool), // From iced
+    FailSafe(FailSafeTrigger),    // From rdev
+    StopScript,                   // From rdev
+    AutoStop(StopReason),         // From the auto-stop watcher: idle or locked
+    PointCaptured(i32, i32),      // From rdev: where the user clicked while capturing
+}
+
+/// Tracks the raw input stream for fail-safe gestures.
+struct FailSafeDetector {
+    screen_size: Option<(f64, f64)>,
+    movement_anchor: Option<(f64, f64, Instant)>,
+    escape_count: u32,
+    last_escape: Option<Instant>,
 }
 
-pub fn subscribe() -> Subscription<HotkeyEvent> {
-    iced::subscription::channel(
-        std::any::TypeId::of::<HotkeyEvent>(),
-        100,
-        |mut output| async move {
-            let (mut sender, mut receiver) = mpsc::channel(100);
-
-            thread::spawn(move || {
-                // Listen loop. This blocks the thread.
-                let mut ctrl = false;
-                let mut alt = false;
-                
-                if let Err(error) = listen(move |event| {
-                    match event.event_type {
-                        EventType::KeyPress(Key::ControlLeft) | EventType::KeyPress(Key::ControlRight) => {
-                            ctrl = true;
-                        }
-                        EventType::KeyRelease(Key::ControlLeft) | EventType::KeyRelease(Key::ControlRight) => {
-                            ctrl = false;
-                        }
-                        EventType::KeyPress(Key::Alt) | EventType::KeyPress(Key::AltGr) => {
-                            alt = true;
-                        }
-                        EventType::KeyRelease(Key::Alt) | EventType::KeyRelease(Key::AltGr) => {
-                            alt = false;
-                        }
-                        EventType::KeyPress(Key::F6) => {
-                            if ctrl && alt {
-                                let _ = sender.try_send(HotkeyEvent::Toggle);
-                            }
-                        }
-                        EventType::Wheel { delta_y, .. } => {
-                            if ctrl && alt {
-                                // Send raw delta
-                                let _ = sender.try_send(HotkeyEvent::IntervalChange(delta_y as i32));
-                            }
-                        }
-                        _ => {}
+impl FailSafeDetector {
+    fn new() -> Self {
+        FailSafeDetector {
+            screen_size: rdev::display_size().ok().map(|(w, h)| (w as f64, h as f64)),
+            movement_anchor: None,
+            escape_count: 0,
+            last_escape: None,
+        }
+    }
+
+    /// `own` moves come from our own glides and paths; only the corner
+    /// counts for those, since the user isn't moving the mouse.
+    fn on_mouse_move(&mut self, x: f64, y: f64, own: bool, config: &FailSafeSettings) -> Option<FailSafeTrigger> {
+        if config.corner_enabled
+            && let Some((width, height)) = self.screen_size
+        {
+            let margin = config.corner_margin_px as f64;
+            let near_x = x <= margin || x >= width - 1.0 - margin;
+            let near_y = y <= margin || y >= height - 1.0 - margin;
+            if near_x && near_y {
+                return Some(FailSafeTrigger::ScreenCorner);
+            }
+        }
+
+        if config.movement_enabled && !own {
+            let now = Instant::now();
+            let window = Duration::from_millis(config.movement_window_ms);
+            match self.movement_anchor {
+                Some((ax, ay, since)) if now.duration_since(since) <= window => {
+                    let distance = ((x - ax).powi(2) + (y - ay).powi(2)).sqrt();
+                    if distance >= config.movement_threshold_px as f64 {
+                        self.movement_anchor = None;
+                        return Some(FailSafeTrigger::MouseMovement);
                     }
-                }) {
-                    eprintln!("Error in rdev::listen: {:?}", error);
                 }
-            });
+                // Start a new measurement window from here.
+                _ => self.movement_anchor = Some((x, y, now)),
+            }
+        }
+
+        None
+    }
+
+    fn on_escape(&mut self, config: &FailSafeSettings) -> Option<FailSafeTrigger> {
+        if !config.escape_enabled {
+            return None;
+        }
+
+        let now = Instant::now();
+        let in_sequence = self
+            .last_escape
+            .is_some_and(|last| now.duration_since(last) <= ESCAPE_SEQUENCE_GAP);
+        self.escape_count = if in_sequence { self.escape_count + 1 } else { 1 };
+        self.last_escape = Some(now);
+
+        if self.escape_count >= config.escape_presses.max(1) {
+            self.escape_count = 0;
+            return Some(FailSafeTrigger::EscapeRepeated);
+        }
+        None
+    }
+}
+
+/// Runs the global input listener on the current thread, calling `on_event`
+/// for every toggle, interval, mini mode, script stop and fail-safe event.
+/// Blocks for the lifetime of the process.
+pub fn run_listener(mut on_event: impl FnMut(HotkeyEvent) + 'static) {
+    let mut ctrl = false;
+    let mut alt = false;
+    let mut shift = false;
+    let mut fail_safe = FailSafeDetector::new();
+
+    if let Err(error) = listen(move |event| {
+        let config = FAIL_SAFE.lock().map(|config| *config).unwrap_or_default();
+
+        let own = clicking::is_own_input(&event.event_type);
+        if !own
+            && let Ok(mut last) = LAST_USER_INPUT.lock()
+        {
+            *last = Instant::now();
+        }
+
+        if let EventType::KeyPress(key) = event.event_type
+            && let Ok(bindings) = TOGGLE_BINDINGS.lock()
+        {
+            let pressed = ToggleBinding { ctrl, alt, shift, key };
+            for (index, binding) in bindings.iter().enumerate() {
+                if *binding == Some(pressed) {
+                    debug!(index, "toggle hotkey pressed");
+                    on_event(HotkeyEvent::Toggle(index));
+                }
+            }
+            if SCRIPT_STOP_BINDING.lock().is_ok_and(|binding| *binding == Some(pressed)) {
+                on_event(HotkeyEvent::StopScript);
+            }
+        }
 
-            while let Some(event) = receiver.next().await {
-                let _ = output.send(event).await;
+        match event.event_type {
+            EventType::KeyPress(Key::ControlLeft) | EventType::KeyPress(Key::ControlRight) => {
+                ctrl = true;
+            }
+            EventType::KeyRelease(Key::ControlLeft) | EventType::KeyRelease(Key::ControlRight) => {
+                ctrl = false;
+            }
+            EventType::KeyPress(Key::Alt) | EventType::KeyPress(Key::AltGr) => {
+                alt = true;
+            }
+            EventType::KeyRelease(Key::Alt) | EventType::KeyRelease(Key::AltGr) => {
+                alt = false;
+            }
+            EventType::KeyPress(Key::ShiftLeft) | EventType::KeyPress(Key::ShiftRight) => {
+                shift = true;
+            }
+            EventType::KeyRelease(Key::ShiftLeft) | EventType::KeyRelease(Key::ShiftRight) => {
+                shift = false;
+            }
+            EventType::KeyPress(Key::KeyM) if ctrl && alt => {
+                on_event(HotkeyEvent::ToggleMiniMode);
+            }
+            // Keyboard alternative to the wheel: Up speeds up, Down slows down.
+            EventType::KeyPress(Key::UpArrow) if ctrl && alt => {
+                on_event(HotkeyEvent::IntervalChange(1));
+            }
+            EventType::KeyPress(Key::DownArrow) if ctrl && alt => {
+                on_event(HotkeyEvent::IntervalChange(-1));
+            }
+            // Escapes pressed by a clicker, pattern or script don't count.
+            EventType::KeyPress(Key::Escape) if !own => {
+                if let Some(trigger) = fail_safe.on_escape(&config) {
+                    warn!(%trigger, "fail-safe triggered");
+                    on_event(HotkeyEvent::FailSafe(trigger));
+                }
+            }
+            EventType::ButtonPress(Button::Left) if !own && CAPTURING.load(Ordering::Relaxed) => {
+                // Button events carry no position; the last move has it.
+                if let Some((x, y)) = RdevBackend.cursor_position() {
+                    on_event(HotkeyEvent::PointCaptured(x.round() as i32, y.round() as i32));
+                }
             }
-            
-            loop {
-                std::future::pending::<()>().await;
+            EventType::MouseMove { x, y } => {
+                clicking::note_cursor(x, y);
+                if let Some(trigger) = fail_safe.on_mouse_move(x, y, own, &config) {
+                    warn!(%trigger, "fail-safe triggered");
+                    on_event(HotkeyEvent::FailSafe(trigger));
+                }
+            }
+            EventType::Wheel { delta_y, .. } if ctrl && alt => {
+                // Send raw delta
+                on_event(HotkeyEvent::IntervalChange(delta_y as i32));
             }
-        },
-    )
+            _ => {}
+        }
+    }) {
+        error!(?error, "global input listener stopped");
+    }
 }
 
-pub fn subscribe_local() -> Subscription<HotkeyEvent> {
-    use iced::event::Status;
+/// Watches for the user going idle or the computer locking, and sends
+/// `HotkeyEvent::AutoStop` on every poll while either lasts, so runs started
+/// meanwhile (e.g. scheduled ones) stop too. Needs `run_listener` running to
+/// see user input. Blocks for the lifetime of the process.
+pub fn run_auto_stop_watcher(mut on_event: impl FnMut(HotkeyEvent)) {
+    let mut was_idle = false;
+    let mut was_locked = false;
 
-    event::listen_with(|event, status| {
-        if status == Status::Captured {
-            return None;
+    loop {
+        thread::sleep(AUTO_STOP_POLL);
+        let config = AUTO_STOP.lock().map(|config| *config).unwrap_or_default();
+
+        let idle = config.idle_secs > 0 && idle_for() >= Duration::from_secs(config.idle_secs);
+        if idle && !was_idle {
+            warn!(idle_secs = config.idle_secs, "user idle, auto-stopping");
+        }
+        if idle {
+            on_event(HotkeyEvent::AutoStop(StopReason::Idle));
         }
+        was_idle = idle;
 
-        match event {
-            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
-                Some(HotkeyEvent::ModifiersChanged(
-                    modifiers.control(),
-                    modifiers.alt(),
-                ))
-            }
-            Event::Mouse(mouse::Event::WheelScrolled { delta }) => match delta {
-                mouse::ScrollDelta::Lines { y, .. } => Some(HotkeyEvent::LocalScroll(y)),
-                mouse::ScrollDelta::Pixels { y, .. } => {
-                    Some(HotkeyEvent::LocalScroll(y / 10.0))
-                }
-            },
-            _ => None,
+        let locked = config.stop_on_lock && platform::session_locked();
+        if locked && !was_locked {
+            warn!("computer locked, auto-stopping");
+        }
+        if locked {
+            on_event(HotkeyEvent::AutoStop(StopReason::Locked));
         }
-    })
-}
\ No newline at end of file
+        was_locked = locked;
+    }
+}
+
+/// Sets `stop` when the script stop hotkey or a fail-safe trigger fires.
+/// Used for headless script runs, where there is no UI listener.
+pub fn spawn_script_stop_listener(hotkey: &Hotkey, stop: Arc<AtomicBool>) {
+    set_script_stop_hotkey(hotkey);
+    thread::spawn(move || {
+        run_listener(move |event| {
+            if matches!(event, HotkeyEvent::StopScript | HotkeyEvent::FailSafe(_)) {
+                stop.store(true, Ordering::Relaxed);
+            }
+        });
+    });
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows_sys::Win32::System::StationsAndDesktops::{
+        CloseDesktop, OpenInputDesktop, SwitchDesktop, DESKTOP_SWITCHDESKTOP,
+    };
+
+    /// The lock screen runs on a secure desktop that other processes can't
+    /// switch to, so failing to switch to the input desktop means locked.
+    pub fn session_locked() -> bool {
+        // SAFETY: the desktop handle is checked before use and closed again.
+        unsafe {
+            let desktop = OpenInputDesktop(0, 0, DESKTOP_SWITCHDESKTOP);
+            if desktop == 0 {
+                return true;
+            }
+            let switched = SwitchDesktop(desktop);
+            CloseDesktop(desktop);
+            switched == 0
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    /// No lock detection here.
+    pub fn session_locked() -> bool {
+        false
+    }
+}
@@ -0,0 +1,70 @@
+//! Iced subscriptions for global hotkeys and window-local input.
+
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::keyboard;
+use iced::mouse;
+use iced::event;
+use iced::{Event, Subscription};
+use std::thread;
+
+use super_clicker::hotkeys::{self, HotkeyEvent};
+
+pub fn subscribe() -> Subscription<HotkeyEvent> {
+    iced::subscription::channel(
+        std::any::TypeId::of::<HotkeyEvent>(),
+        100,
+        |mut output| async move {
+            let (mut sender, mut receiver) = mpsc::channel(100);
+
+            // Idle and lock checks report through the same channel.
+            let mut watcher_sender = sender.clone();
+            thread::spawn(move || {
+                hotkeys::run_auto_stop_watcher(move |event| {
+                    let _ = watcher_sender.try_send(event);
+                });
+            });
+
+            // Listen loop. This blocks the thread.
+            thread::spawn(move || {
+                hotkeys::run_listener(move |event| {
+                    let _ = sender.try_send(event);
+                });
+            });
+
+            while let Some(event) = receiver.next().await {
+                let _ = output.send(event).await;
+            }
+            
+            loop {
+                std::future::pending::<()>().await;
+            }
+        },
+    )
+}
+
+pub fn subscribe_local() -> Subscription<HotkeyEvent> {
+    use iced::event::Status;
+
+    event::listen_with(|event, status| {
+        if status == Status::Captured {
+            return None;
+        }
+
+        match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                Some(HotkeyEvent::ModifiersChanged(
+                    modifiers.control(),
+                    modifiers.alt(),
+                ))
+            }
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => match delta {
+                mouse::ScrollDelta::Lines { y, .. } => Some(HotkeyEvent::LocalScroll(y)),
+                mouse::ScrollDelta::Pixels { y, .. } => {
+                    Some(HotkeyEvent::LocalScroll(y / 10.0))
+                }
+            },
+            _ => None,
+        }
+    })
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+//! Human-readable key names used in settings and hotkey bindings.
+
+use rdev::Key;
+
+/// Canonical names, in the form they are written back to settings.
+const KEY_NAMES: &[(&str, Key)] = &[
+    ("A", Key::KeyA), ("B", Key::KeyB), ("C", Key::KeyC), ("D", Key::KeyD),
+    ("E", Key::KeyE), ("F", Key::KeyF), ("G", Key::KeyG), ("H", Key::KeyH),
+    ("I", Key::KeyI), ("J", Key::KeyJ), ("K", Key::KeyK), ("L", Key::KeyL),
+    ("M", Key::KeyM), ("N", Key::KeyN), ("O", Key::KeyO), ("P", Key::KeyP),
+    ("Q", Key::KeyQ), ("R", Key::KeyR), ("S", Key::KeyS), ("T", Key::KeyT),
+    ("U", Key::KeyU), ("V", Key::KeyV), ("W", Key::KeyW), ("X", Key::KeyX),
+    ("Y", Key::KeyY), ("Z", Key::KeyZ),
+    ("0", Key::Num0), ("1", Key::Num1), ("2", Key::Num2), ("3", Key::Num3),
+    ("4", Key::Num4), ("5", Key::Num5), ("6", Key::Num6), ("7", Key::Num7),
+    ("8", Key::Num8), ("9", Key::Num9),
+    ("F1", Key::F1), ("F2", Key::F2), ("F3", Key::F3), ("F4", Key::F4),
+    ("F5", Key::F5), ("F6", Key::F6), ("F7", Key::F7), ("F8", Key::F8),
+    ("F9", Key::F9), ("F10", Key::F10), ("F11", Key::F11), ("F12", Key::F12),
+    ("Space", Key::Space), ("Enter", Key::Return), ("Tab", Key::Tab),
+    ("Escape", Key::Escape), ("Backspace", Key::Backspace),
+    ("Up", Key::UpArrow), ("Down", Key::DownArrow),
+    ("Left", Key::LeftArrow), ("Right", Key::RightArrow),
+    ("Home", Key::Home), ("End", Key::End),
+    ("PageUp", Key::PageUp), ("PageDown", Key::PageDown),
+    ("Insert", Key::Insert), ("Delete", Key::Delete),
+    ("Shift", Key::ShiftLeft), ("Ctrl", Key::ControlLeft), ("Alt", Key::Alt),
+];
+
+/// Looks up a key by name, ignoring case. Accepts a few common aliases.
+pub fn parse_key(name: &str) -> Option<Key> {
+    let name = name.trim();
+    let alias = match name.to_ascii_lowercase().as_str() {
+        "return" => "Enter",
+        "esc" => "Escape",
+        "control" => "Ctrl",
+        "del" => "Delete",
+        _ => name,
+    };
+
+    KEY_NAMES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(alias))
+        .map(|(_, key)| *key)
+}
+
+/// Canonical name of a key, if it is one we know how to write.
+pub fn key_name(key: Key) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(_, known)| *known == key).map(|(name, _)| *name)
+}
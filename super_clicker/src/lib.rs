@@ -0,0 +1,32 @@
+//! SuperClicker's clicking engine, profiles and input handling, usable
+//! without the GUI.
+//!
+//! Build with `default-features = false` to leave out the `gui` feature
+//! (and with it iced), e.g. to embed a [`clicking::ClickingEngine`] or read
+//! SuperClicker profiles from another tool:
+//!
+//! ```no_run
+//! use super_clicker::clicking::ClickingEngine;
+//! use super_clicker::settings::Settings;
+//!
+//! let settings = Settings::load().unwrap_or_default();
+//! let engine = ClickingEngine::new();
+//! engine.start(&settings.clickers[0]);
+//! std::thread::sleep(std::time::Duration::from_secs(1));
+//! engine.stop();
+//! ```
+
+pub mod benchmark;
+pub mod bundle;
+pub mod clicking;
+pub mod clock;
+pub mod history;
+pub mod hotkeys;
+pub mod keys;
+pub mod logging;
+pub mod macros;
+pub mod motion;
+pub mod scheduler;
+pub mod scripting;
+pub mod settings;
+pub mod sound;
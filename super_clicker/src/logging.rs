@@ -0,0 +1,136 @@
+//! Diagnostics log.
+//!
+//! `tracing` events from the app, the engine and the hotkey listener go to
+//! stderr and to a daily log file in the `logs` folder of the config
+//! directory. Only the last `MAX_LOG_FILES` files are kept. The level comes
+//! from `Settings::log_level` and can be changed while running.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+use super::settings::{LogLevel, Settings};
+
+const LOG_PREFIX: &str = "super_clicker";
+const LOG_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+
+static LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+pub fn log_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(Settings::config_dir()?.join("logs"))
+}
+
+/// Installs the global subscriber. Keep the returned guard alive until exit;
+/// dropping it flushes the log file.
+pub fn init(level: LogLevel) -> Option<WorkerGuard> {
+    let (filter, handle) = reload::Layer::new(level_filter(level));
+
+    let appender = log_dir().and_then(|dir| {
+        // The appender prunes old files on start and complains if there is no folder yet.
+        fs::create_dir_all(&dir)?;
+        Ok(RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_PREFIX)
+            .filename_suffix(LOG_SUFFIX)
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)?)
+    });
+    let (file_layer, guard) = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(fmt::layer().with_ansi(false).with_writer(writer)), Some(guard))
+        }
+        Err(error) => {
+            eprintln!("Could not open log file: {}", error);
+            (None, None)
+        }
+    };
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .try_init();
+    if installed.is_ok() {
+        let _ = LEVEL.set(handle);
+    }
+    guard
+}
+
+/// Applies a new level to the running subscriber.
+pub fn set_level(level: LogLevel) {
+    if let Some(handle) = LEVEL.get() {
+        let _ = handle.reload(level_filter(level));
+    }
+}
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Error => LevelFilter::ERROR,
+        LogLevel::Warn => LevelFilter::WARN,
+        LogLevel::Info => LevelFilter::INFO,
+        LogLevel::Debug => LevelFilter::DEBUG,
+        LogLevel::Trace => LevelFilter::TRACE,
+    }
+}
+
+/// The newest log file. Daily files are named `super_clicker.YYYY-MM-DD.log`,
+/// so the newest sorts last.
+pub fn latest_file() -> Option<PathBuf> {
+    fs::read_dir(log_dir().ok()?)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            name.starts_with(LOG_PREFIX) && name.ends_with(LOG_SUFFIX)
+        })
+        .max()
+}
+
+/// The last `count` lines of the newest log file.
+pub fn recent_lines(count: usize) -> Vec<String> {
+    latest_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| last_lines(&content, count))
+        .unwrap_or_default()
+}
+
+fn last_lines(content: &str, count: usize) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(count)..].iter().map(|line| line.to_string()).collect()
+}
+
+/// Opens the newest log file in the system's default viewer.
+pub fn open_latest() -> Result<(), Box<dyn std::error::Error>> {
+    let path = latest_file().ok_or("No log file yet")?;
+    let opener = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(opener).arg(path).spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_last_lines() {
+        let content = "one\ntwo\nthree\n";
+        assert_eq!(last_lines(content, 2), vec!["two", "three"]);
+        assert_eq!(last_lines(content, 10), vec!["one", "two", "three"]);
+        assert!(last_lines("", 5).is_empty());
+    }
+}
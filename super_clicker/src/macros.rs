@@ -0,0 +1,564 @@
+//! Line-oriented text format for click patterns, for editing by hand.
+//!
+//! ```text
+//! # Comments start with '#'
+//! move 100 200
+//! repeat 10 {
+//!     click left
+//!     wait 50ms
+//! }
+//! press E
+//! scroll down 3
+//! wait 1s
+//! ```
+//!
+//! A macro flattens to the `PatternStep` list the clicking engine plays.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use super::keys;
+use super::settings::{Action, MouseButton, PatternStep, ScrollDirection, Settings, SCROLL_NOTCHES};
+
+/// Upper bound on the flattened length, so `repeat` can't exhaust memory.
+const MAX_STEPS: usize = 100_000;
+
+/// Deepest `repeat` nesting accepted. Parsing and flattening recurse per level.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroItem {
+    Step(PatternStep),
+    Repeat(u32, Vec<MacroItem>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Macro {
+    pub items: Vec<MacroItem>,
+}
+
+/// Where and why parsing failed. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<'a> {
+    Word(&'a str),
+    Open,
+    Close,
+    EndOfLine,
+}
+
+#[derive(Debug, Clone)]
+struct Spanned<'a> {
+    token: Token<'a>,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(text: &str) -> Vec<Spanned<'_>> {
+    let mut tokens = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let code = line.split('#').next().unwrap_or("");
+        let mut rest = code;
+
+        loop {
+            let trimmed = rest.trim_start();
+            if trimmed.is_empty() {
+                break;
+            }
+            // Columns count characters, not bytes.
+            let column = code[..code.len() - trimmed.len()].chars().count() + 1;
+            let (token, length) = match trimmed.chars().next() {
+                Some('{') => (Token::Open, 1),
+                Some('}') => (Token::Close, 1),
+                _ => {
+                    let length = trimmed
+                        .find(|c: char| c.is_whitespace() || c == '{' || c == '}')
+                        .unwrap_or(trimmed.len());
+                    (Token::Word(&trimmed[..length]), length)
+                }
+            };
+            tokens.push(Spanned { token, line: line_number, column });
+            rest = &trimmed[length..];
+        }
+
+        tokens.push(Spanned {
+            token: Token::EndOfLine,
+            line: line_number,
+            column: line.chars().count() + 1,
+        });
+    }
+
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<Spanned<'a>>,
+    position: usize,
+    /// Position reported for errors at the end of the input.
+    end: (usize, usize),
+    /// `repeat` blocks open around the current position.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Spanned<'a>> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Spanned<'a>> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error_at(&self, spanned: Option<&Spanned<'_>>, message: String) -> ParseError {
+        let (line, column) = spanned.map_or(self.end, |spanned| (spanned.line, spanned.column));
+        ParseError { line, column, message }
+    }
+
+    /// Items up to the end of input, or up to a `}` when `nested`.
+    fn items(&mut self, nested: bool) -> Result<Vec<MacroItem>, ParseError> {
+        let mut items = Vec::new();
+
+        loop {
+            let Some(spanned) = self.peek().cloned() else {
+                return if nested {
+                    Err(self.error_at(None, String::from("missing '}'")))
+                } else {
+                    Ok(items)
+                };
+            };
+
+            match spanned.token {
+                Token::EndOfLine => {
+                    self.position += 1;
+                }
+                Token::Close if nested => {
+                    self.position += 1;
+                    return Ok(items);
+                }
+                Token::Close | Token::Open => {
+                    return Err(self.error_at(Some(&spanned), format!("unexpected '{}'", token_text(&spanned.token))));
+                }
+                Token::Word(command) => {
+                    self.position += 1;
+                    items.push(self.command(command, &spanned)?);
+                    self.end_of_statement()?;
+                }
+            }
+        }
+    }
+
+    /// A statement ends at the end of the line or before a closing brace.
+    fn end_of_statement(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            None | Some(Spanned { token: Token::EndOfLine | Token::Close, .. }) => Ok(()),
+            Some(spanned) => Err(self.error_at(
+                Some(spanned),
+                format!("unexpected '{}' after command", token_text(&spanned.token)),
+            )),
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<(&'a str, Spanned<'a>), ParseError> {
+        match self.next() {
+            Some(spanned @ Spanned { token: Token::Word(word), .. }) => Ok((word, spanned)),
+            other => {
+                self.position -= 1;
+                Err(self.error_at(other.as_ref(), format!("expected {}", what)))
+            }
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, what: &str) -> Result<T, ParseError> {
+        let (word, spanned) = self.word(what)?;
+        word.parse()
+            .map_err(|_| self.error_at(Some(&spanned), format!("expected {}, found '{}'", what, word)))
+    }
+
+    fn command(&mut self, command: &str, at: &Spanned<'_>) -> Result<MacroItem, ParseError> {
+        let step = match command.to_ascii_lowercase().as_str() {
+            "click" => {
+                let (name, spanned) = self.word("a mouse button (left, middle or right)")?;
+                let button = match name.to_ascii_lowercase().as_str() {
+                    "left" => MouseButton::Left,
+                    "middle" => MouseButton::Middle,
+                    "right" => MouseButton::Right,
+                    _ => return Err(self.error_at(Some(&spanned), format!("unknown mouse button '{}'", name))),
+                };
+                PatternStep::Act(Action::Click(button))
+            }
+            "press" => {
+                let (name, spanned) = self.word("a key name")?;
+                let Some(key) = keys::parse_key(name).and_then(keys::key_name) else {
+                    return Err(self.error_at(Some(&spanned), format!("unknown key '{}'", name)));
+                };
+                PatternStep::Act(Action::Press(key.to_string()))
+            }
+            "scroll" => {
+                let (name, spanned) = self.word("a scroll direction (up, down, left or right)")?;
+                let Some(direction) = ScrollDirection::from_name(name) else {
+                    return Err(self.error_at(Some(&spanned), format!("unknown scroll direction '{}'", name)));
+                };
+                // The notch count is optional and defaults to one.
+                let notches = match self.peek() {
+                    Some(Spanned { token: Token::Word(_), .. }) => {
+                        let (word, spanned) = self.word("a number of notches")?;
+                        match word.parse() {
+                            Ok(notches) if SCROLL_NOTCHES.contains(&notches) => notches,
+                            _ => {
+                                let message = format!(
+                                    "expected {} to {} notches, found '{}'",
+                                    SCROLL_NOTCHES.start(),
+                                    SCROLL_NOTCHES.end(),
+                                    word
+                                );
+                                return Err(self.error_at(Some(&spanned), message));
+                            }
+                        }
+                    }
+                    _ => 1,
+                };
+                PatternStep::Act(Action::Scroll(direction, notches))
+            }
+            "move" => {
+                let x = self.number("an x coordinate")?;
+                let y = self.number("a y coordinate")?;
+                PatternStep::MoveTo(x, y)
+            }
+            "wait" => {
+                let (duration, spanned) = self.word("a duration such as 50ms or 2s")?;
+                let Some(ms) = parse_duration_ms(duration) else {
+                    return Err(self.error_at(Some(&spanned), format!("invalid duration '{}'", duration)));
+                };
+                PatternStep::Wait(ms)
+            }
+            "repeat" => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error_at(Some(at), format!("repeats nested more than {} deep", MAX_DEPTH)));
+                }
+                let count = self.number("a repeat count")?;
+                match self.next() {
+                    Some(Spanned { token: Token::Open, .. }) => {}
+                    other => {
+                        self.position -= 1;
+                        return Err(self.error_at(other.as_ref(), String::from("expected '{'")));
+                    }
+                }
+                self.depth += 1;
+                let body = self.items(true)?;
+                self.depth -= 1;
+                return Ok(MacroItem::Repeat(count, body));
+            }
+            _ => return Err(self.error_at(Some(at), format!("unknown command '{}'", command))),
+        };
+        Ok(MacroItem::Step(step))
+    }
+}
+
+fn token_text<'a>(token: &Token<'a>) -> &'a str {
+    match token {
+        Token::Word(word) => word,
+        Token::Open => "{",
+        Token::Close => "}",
+        Token::EndOfLine => "end of line",
+    }
+}
+
+/// "50ms", "2s" or a bare number of milliseconds.
+fn parse_duration_ms(value: &str) -> Option<u64> {
+    let value = value.to_ascii_lowercase();
+    if let Some(ms) = value.strip_suffix("ms") {
+        ms.parse().ok()
+    } else if let Some(secs) = value.strip_suffix('s') {
+        secs.parse::<u64>().ok()?.checked_mul(1000)
+    } else {
+        value.parse().ok()
+    }
+}
+
+fn flattened_len(items: &[MacroItem]) -> usize {
+    items
+        .iter()
+        .map(|item| match item {
+            MacroItem::Step(_) => 1,
+            MacroItem::Repeat(count, body) => (*count as usize).saturating_mul(flattened_len(body)),
+        })
+        .fold(0, usize::saturating_add)
+}
+
+fn flatten_into(items: &[MacroItem], steps: &mut Vec<PatternStep>) {
+    for item in items {
+        match item {
+            MacroItem::Step(step) => steps.push(step.clone()),
+            MacroItem::Repeat(count, body) => {
+                for _ in 0..*count {
+                    flatten_into(body, steps);
+                }
+            }
+        }
+    }
+}
+
+impl Macro {
+    pub fn parse(text: &str) -> Result<Macro, ParseError> {
+        let tokens = tokenize(text);
+        let end = tokens.last().map_or((1, 1), |last| (last.line, last.column));
+        let mut parser = Parser { tokens, position: 0, end, depth: 0 };
+        let items = parser.items(false)?;
+
+        if flattened_len(&items) > MAX_STEPS {
+            return Err(ParseError {
+                line: 1,
+                column: 1,
+                message: format!("macro expands to more than {} steps", MAX_STEPS),
+            });
+        }
+        Ok(Macro { items })
+    }
+
+    /// A macro of plain steps, e.g. from a clicker's pattern.
+    pub fn from_steps(steps: &[PatternStep]) -> Macro {
+        Macro {
+            items: steps.iter().cloned().map(MacroItem::Step).collect(),
+        }
+    }
+
+    /// The flat step list played by the clicking engine.
+    pub fn to_steps(&self) -> Vec<PatternStep> {
+        let mut steps = Vec::new();
+        flatten_into(&self.items, &mut steps);
+        steps
+    }
+
+    /// Macro files live in the `macros` folder of the config directory.
+    fn dir() -> Result<PathBuf, Box<dyn Error>> {
+        Ok(Settings::config_dir()?.join("macros"))
+    }
+
+    /// File for the macro called `name`, always inside the macros folder.
+    pub fn path(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        Ok(Self::dir()?.join(format!("{}.txt", file_stem(name))))
+    }
+
+    /// Names of the macro files in the macros folder, sorted.
+    pub fn list() -> Vec<String> {
+        let Ok(entries) = Self::dir().and_then(|dir| Ok(fs::read_dir(dir)?)) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn load(name: &str) -> Result<Macro, Box<dyn Error>> {
+        let text = fs::read_to_string(Self::path(name)?)?;
+        Ok(Macro::parse(&text)?)
+    }
+
+    pub fn save(&self, name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let path = Self::path(name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, self.to_string())?;
+        Ok(path)
+    }
+}
+
+/// `name` as a file name that stays in its folder on every platform:
+/// separators and other forbidden characters become `_`, trailing dots and
+/// spaces are dropped, and empty or Windows device names (`CON`, `COM1`, ...)
+/// get a leading `_`. Names that are already safe come back unchanged.
+pub fn file_stem(name: &str) -> String {
+    const FORBIDDEN: &str = "<>:\"/\\|?*";
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_control() || FORBIDDEN.contains(c) { '_' } else { c })
+        .collect();
+    let stem = stem.trim_end_matches(['.', ' ']);
+
+    let device = stem.split('.').next().unwrap_or_default().trim_end().to_ascii_uppercase();
+    let reserved = matches!(device.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || (device.len() == 4
+            && (device.starts_with("COM") || device.starts_with("LPT"))
+            && device.as_bytes()[3].is_ascii_digit());
+    if stem.is_empty() || reserved {
+        format!("_{}", stem)
+    } else {
+        stem.to_string()
+    }
+}
+
+fn write_items(f: &mut fmt::Formatter<'_>, items: &[MacroItem], depth: usize) -> fmt::Result {
+    let indent = "    ".repeat(depth);
+    for item in items {
+        match item {
+            MacroItem::Step(PatternStep::Act(Action::Click(button))) => {
+                let name = match button {
+                    MouseButton::Left => "left",
+                    MouseButton::Middle => "middle",
+                    MouseButton::Right => "right",
+                };
+                writeln!(f, "{}click {}", indent, name)?;
+            }
+            MacroItem::Step(PatternStep::Act(Action::Press(key))) => writeln!(f, "{}press {}", indent, key)?,
+            MacroItem::Step(PatternStep::Act(Action::Scroll(direction, 1))) => {
+                writeln!(f, "{}scroll {}", indent, direction.name())?;
+            }
+            MacroItem::Step(PatternStep::Act(Action::Scroll(direction, notches))) => {
+                writeln!(f, "{}scroll {} {}", indent, direction.name(), notches)?;
+            }
+            MacroItem::Step(PatternStep::MoveTo(x, y)) => writeln!(f, "{}move {} {}", indent, x, y)?,
+            MacroItem::Step(PatternStep::Wait(ms)) if *ms >= 1000 && ms % 1000 == 0 => {
+                writeln!(f, "{}wait {}s", indent, ms / 1000)?;
+            }
+            MacroItem::Step(PatternStep::Wait(ms)) => writeln!(f, "{}wait {}ms", indent, ms)?,
+            MacroItem::Repeat(count, body) => {
+                writeln!(f, "{}repeat {} {{", indent, count)?;
+                write_items(f, body, depth + 1)?;
+                writeln!(f, "{}}}", indent)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Formats in the canonical text form, which parses back to the same macro.
+impl fmt::Display for Macro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_items(f, &self.items, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stems_stay_in_the_folder() {
+        assert_eq!(file_stem("Clicker 1"), "Clicker 1");
+        assert_eq!(file_stem("../../x"), ".._.._x");
+        assert_eq!(file_stem("/etc/passwd"), "_etc_passwd");
+        assert_eq!(file_stem("C:\\temp"), "C__temp");
+        assert_eq!(file_stem(".."), "_");
+        assert_eq!(file_stem("con"), "_con");
+        assert_eq!(file_stem("LPT1.backup"), "_LPT1.backup");
+        assert_eq!(file_stem("COM10"), "COM10");
+    }
+
+    fn error(text: &str) -> (usize, usize) {
+        let error = Macro::parse(text).expect_err("should not parse");
+        (error.line, error.column)
+    }
+
+    #[test]
+    fn parses_and_flattens_repeats() {
+        let text = "move 100 200\nrepeat 2 {\n  click left # first\n  wait 50ms\n}\npress e\nwait 1s\n";
+        let steps = Macro::parse(text).unwrap().to_steps();
+
+        assert_eq!(
+            steps,
+            vec![
+                PatternStep::MoveTo(100, 200),
+                PatternStep::Act(Action::Click(MouseButton::Left)),
+                PatternStep::Wait(50),
+                PatternStep::Act(Action::Click(MouseButton::Left)),
+                PatternStep::Wait(50),
+                PatternStep::Act(Action::Press(String::from("E"))),
+                PatternStep::Wait(1000),
+            ]
+        );
+    }
+
+    #[test]
+    fn scroll_notches_default_to_one() {
+        let steps = Macro::parse("scroll UP\nscroll left 5").unwrap().to_steps();
+        assert_eq!(
+            steps,
+            vec![
+                PatternStep::Act(Action::Scroll(ScrollDirection::Up, 1)),
+                PatternStep::Act(Action::Scroll(ScrollDirection::Left, 5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeat_body_may_share_a_line() {
+        let steps = Macro::parse("repeat 3 { click right }").unwrap().to_steps();
+        assert_eq!(steps, vec![PatternStep::Act(Action::Click(MouseButton::Right)); 3]);
+    }
+
+    #[test]
+    fn formatted_text_round_trips() {
+        let text = "move -5 10\nrepeat 10 {\n    click middle\n    wait 40ms\n    repeat 2 {\n        press Space\n    }\n}\nwait 2s\n";
+        let parsed = Macro::parse(text).unwrap();
+        assert_eq!(parsed.to_string(), text);
+        assert_eq!(Macro::parse(&parsed.to_string()).unwrap(), parsed);
+    }
+
+    #[test]
+    fn flat_step_lists_round_trip() {
+        let steps = vec![
+            PatternStep::Act(Action::Click(MouseButton::Left)),
+            PatternStep::Wait(1500),
+            PatternStep::MoveTo(3, 4),
+            PatternStep::Act(Action::Press(String::from("F6"))),
+            PatternStep::Act(Action::Scroll(ScrollDirection::Down, 1)),
+            PatternStep::Act(Action::Scroll(ScrollDirection::Right, 4)),
+        ];
+        let text = Macro::from_steps(&steps).to_string();
+        assert_eq!(Macro::parse(&text).unwrap().to_steps(), steps);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_token() {
+        assert_eq!(error("click left\nclick sideways"), (2, 7));
+        assert_eq!(error("wait fast"), (1, 6));
+        assert_eq!(error("  jump"), (1, 3));
+        assert_eq!(error("move 10"), (1, 8));
+        assert_eq!(error("repeat 2 {\n  click left\n"), (2, 13));
+        assert_eq!(error("click left }"), (1, 12));
+        assert_eq!(error("click left right"), (1, 12));
+        assert_eq!(error("scroll sideways"), (1, 8));
+        assert_eq!(error("scroll up lots"), (1, 11));
+        assert_eq!(error("scroll up 0"), (1, 11));
+        assert_eq!(error("scroll up 274"), (1, 11));
+    }
+
+    #[test]
+    fn huge_expansions_are_rejected() {
+        assert!(Macro::parse("repeat 1000 { repeat 1000 { click left } }").is_err());
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected() {
+        let nested = |depth: usize| format!("{}click left\n{}", "repeat 1 {\n".repeat(depth), "}\n".repeat(depth));
+        assert!(Macro::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(error(&nested(MAX_DEPTH + 1)), (MAX_DEPTH + 1, 1));
+        // Deep enough to overflow the stack without the limit.
+        assert!(Macro::parse(&nested(100_000)).is_err());
+    }
+}
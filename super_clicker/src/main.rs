@@ -1,18 +1,69 @@
 #![windows_subsystem = "windows"]
 
+// GUI modules; the engine, profiles and input handling live in the library.
 mod app;
+mod input;
 mod ui;
-mod clicking;
-mod hotkeys;
-mod settings;
+mod system_theme;
 mod theme; // Declare the theme module
 
 use iced::{Application, Settings};
 use app::SuperClicker;
+use super_clicker::{benchmark, logging, scripting, settings};
+use tracing::info;
 
 fn main() -> iced::Result {
+    // `--config-dir <path>` goes first, since everything else reads the config.
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        attach_parent_console();
+    }
+    if let Some(index) = args.iter().position(|arg| arg == "--config-dir") {
+        let Some(dir) = args.get(index + 1).cloned() else {
+            eprintln!("--config-dir needs a folder");
+            std::process::exit(2);
+        };
+        settings::set_config_dir(dir.into());
+        args.drain(index..=index + 1);
+    }
+
+    // Held until exit so buffered log lines reach the file.
+    let log_guard = logging::init(settings::Settings::load().unwrap_or_default().log_level);
+    if let Ok((dir, source)) = settings::Settings::config_location() {
+        info!(dir = %dir.display(), %source, "config directory");
+    }
+
+    // Command line modes run without a window:
+    // `--run-script <name or path>` runs a script,
+    // `--benchmark [--calibrate]` measures timing accuracy (and saves the offset).
+    let code = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["--run-script", script] => Some(scripting::run_from_cli(script)),
+        ["--benchmark"] => Some(benchmark::run_from_cli(false)),
+        ["--benchmark", "--calibrate"] => Some(benchmark::run_from_cli(true)),
+        _ => None,
+    };
+    if let Some(code) = code {
+        drop(log_guard);
+        std::process::exit(code);
+    }
+
     let mut settings = Settings::default();
-    settings.window.size = iced::Size::new(400.0, 350.0);
+    settings.window.size = ui::WINDOW_SIZE;
     settings.window.resizable = false;
+    // The app saves the mini strip position before closing the window itself.
+    settings.window.exit_on_close_request = false;
     SuperClicker::run(settings)
-}
\ No newline at end of file
+}
+/// A `windows` subsystem program has no console, so command line output
+/// would go nowhere. Borrow the one of the shell that started us, if any.
+#[cfg(windows)]
+fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // SAFETY: takes no pointers; fails harmlessly when started from Explorer.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
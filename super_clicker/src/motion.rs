@@ -0,0 +1,237 @@
+//! Cursor movement geometry: jiggle offsets, positions along a path or circle,
+//! curved glides between two points and random points in a click area.
+//!
+//! Everything here is pure math; the clicking engine turns the points into
+//! `MouseMove` events.
+
+use rand::Rng;
+use std::f64::consts::TAU;
+use std::time::Duration;
+
+use super::settings::{AreaShape, ClickArea, Motion, Spread};
+
+/// How far a glide's curve bends away from the straight line, as a fraction
+/// of its length.
+const GLIDE_BEND: f64 = 0.15;
+
+/// Offset of the `index`th jiggle. Four jiggles go round a square, so the
+/// cursor never drifts away.
+pub fn jiggle_offset(index: usize, pixels: u32) -> (i32, i32) {
+    let pixels = pixels as i32;
+    match index % 4 {
+        0 => (pixels, 0),
+        1 => (0, pixels),
+        2 => (-pixels, 0),
+        _ => (0, -pixels),
+    }
+}
+
+/// Where a path or circle motion puts the cursor after `elapsed`, or `None`
+/// for motions that don't follow a path.
+pub fn path_point(motion: &Motion, elapsed: Duration) -> Option<(f64, f64)> {
+    match motion {
+        Motion::Path { points, speed } => {
+            let distance = elapsed.as_secs_f64() * *speed as f64;
+            polyline_point(points, distance)
+        }
+        Motion::Circle { center, radius, speed } => {
+            let (cx, cy) = (center.0 as f64, center.1 as f64);
+            if *radius == 0 {
+                return Some((cx, cy));
+            }
+            let radius = *radius as f64;
+            let angle = (elapsed.as_secs_f64() * *speed as f64 / radius) % TAU;
+            Some((cx + radius * angle.cos(), cy + radius * angle.sin()))
+        }
+        Motion::Still | Motion::Jiggle { .. } => None,
+    }
+}
+
+/// The point `distance` pixels along `points`, walked as a closed loop.
+fn polyline_point(points: &[(i32, i32)], distance: f64) -> Option<(f64, f64)> {
+    let first = *points.first()?;
+    let to_f64 = |(x, y): (i32, i32)| (x as f64, y as f64);
+    let segments: Vec<((f64, f64), (f64, f64))> = points
+        .iter()
+        .zip(points.iter().skip(1).chain([&first]))
+        .map(|(&from, &to)| (to_f64(from), to_f64(to)))
+        .collect();
+    let total: f64 = segments.iter().map(|&(from, to)| length(from, to)).sum();
+    if total == 0.0 {
+        return Some(to_f64(first));
+    }
+
+    let mut remaining = distance.rem_euclid(total);
+    for (from, to) in segments {
+        let segment = length(from, to);
+        if remaining <= segment && segment > 0.0 {
+            return Some(lerp(from, to, remaining / segment));
+        }
+        remaining -= segment;
+    }
+    Some(to_f64(first))
+}
+
+/// The point at `t` (0 to 1) of a glide from `from` to `to`: a cubic Bezier
+/// curve bent to one side, eased so it starts and ends slowly.
+pub fn glide_point(from: (f64, f64), to: (f64, f64), t: f64) -> (f64, f64) {
+    let t = t.clamp(0.0, 1.0);
+    let t = t * t * (3.0 - 2.0 * t);
+
+    // Control points a third and two thirds along, pushed off the line.
+    let bend = length(from, to) * GLIDE_BEND;
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let norm = dx.hypot(dy).max(f64::EPSILON);
+    let (nx, ny) = (-dy / norm * bend, dx / norm * bend);
+    let c1 = (from.0 + dx / 3.0 + nx, from.1 + dy / 3.0 + ny);
+    let c2 = (from.0 + dx * 2.0 / 3.0 + nx, from.1 + dy * 2.0 / 3.0 + ny);
+
+    let u = 1.0 - t;
+    let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    let points = [from, c1, c2, to];
+    points
+        .iter()
+        .zip(weights)
+        .fold((0.0, 0.0), |(x, y), (point, weight)| (x + point.0 * weight, y + point.1 * weight))
+}
+
+/// A random point inside `area`.
+pub fn area_point(area: &ClickArea, rng: &mut impl Rng) -> (i32, i32) {
+    let (x, y) = match area.shape {
+        AreaShape::Rectangle => {
+            let (left, right) = (area.from.0.min(area.to.0), area.from.0.max(area.to.0));
+            let (top, bottom) = (area.from.1.min(area.to.1), area.from.1.max(area.to.1));
+            (
+                left as f64 + spread_fraction(area.spread, rng) * (right - left) as f64,
+                top as f64 + spread_fraction(area.spread, rng) * (bottom - top) as f64,
+            )
+        }
+        AreaShape::Circle => {
+            let center = (area.from.0 as f64, area.from.1 as f64);
+            let radius = length(center, (area.to.0 as f64, area.to.1 as f64));
+            let angle = rng.r#gen::<f64>() * TAU;
+            // The square root spreads uniform points evenly over the disc
+            // instead of bunching them at the center.
+            let distance = match area.spread {
+                Spread::Uniform => radius * rng.r#gen::<f64>().sqrt(),
+                Spread::Centered => radius * (2.0 * spread_fraction(Spread::Centered, rng) - 1.0).abs(),
+            };
+            (center.0 + distance * angle.cos(), center.1 + distance * angle.sin())
+        }
+    };
+    (x.round() as i32, y.round() as i32)
+}
+
+/// 0 to 1. Centered averages two draws, which peaks at 0.5.
+fn spread_fraction(spread: Spread, rng: &mut impl Rng) -> f64 {
+    match spread {
+        Spread::Uniform => rng.r#gen(),
+        Spread::Centered => (rng.r#gen::<f64>() + rng.r#gen::<f64>()) / 2.0,
+    }
+}
+
+fn length(from: (f64, f64), to: (f64, f64)) -> f64 {
+    (to.0 - from.0).hypot(to.1 - from.1)
+}
+
+fn lerp(from: (f64, f64), to: (f64, f64), t: f64) -> (f64, f64) {
+    (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6
+    }
+
+    #[test]
+    fn jiggle_goes_round_a_square() {
+        let (x, y) = (0..4).map(|index| jiggle_offset(index, 3)).fold((0, 0), |(x, y), (dx, dy)| (x + dx, y + dy));
+        assert_eq!((x, y), (0, 0));
+        assert_eq!(jiggle_offset(5, 2), (0, 2));
+    }
+
+    #[test]
+    fn path_loops_at_speed() {
+        let motion = Motion::Path {
+            points: vec![(0, 0), (100, 0), (100, 100)],
+            speed: 100,
+        };
+        assert!(close(path_point(&motion, Duration::from_millis(500)).unwrap(), (50.0, 0.0)));
+        assert!(close(path_point(&motion, Duration::from_millis(1500)).unwrap(), (100.0, 50.0)));
+        // Back along the closing diagonal, then round again.
+        let loop_secs = 2.0 + 2f64.sqrt();
+        assert!(close(path_point(&motion, Duration::from_secs_f64(loop_secs + 0.25)).unwrap(), (25.0, 0.0)));
+        assert_eq!(path_point(&Motion::Jiggle { pixels: 3 }, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn circle_keeps_its_radius() {
+        let motion = Motion::Circle {
+            center: (200, 200),
+            radius: 50,
+            speed: 400,
+        };
+        for ms in [0, 130, 777, 5000] {
+            let (x, y) = path_point(&motion, Duration::from_millis(ms)).unwrap();
+            assert!(((x - 200.0).hypot(y - 200.0) - 50.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn glide_ends_on_target_and_bends() {
+        let (from, to) = ((0.0, 0.0), (200.0, 0.0));
+        assert!(close(glide_point(from, to, 0.0), from));
+        assert!(close(glide_point(from, to, 1.0), to));
+        let (x, y) = glide_point(from, to, 0.5);
+        assert!((x - 100.0).abs() < 1e-6);
+        assert!(y.abs() > 1.0);
+    }
+
+    #[test]
+    fn area_points_stay_inside() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let rectangle = ClickArea {
+            shape: AreaShape::Rectangle,
+            from: (110, 60),
+            to: (10, 20),
+            spread: Spread::Uniform,
+        };
+        let circle = ClickArea {
+            shape: AreaShape::Circle,
+            from: (500, 500),
+            to: (530, 540),
+            spread: Spread::Centered,
+        };
+        for _ in 0..1000 {
+            let (x, y) = area_point(&rectangle, &mut rng);
+            assert!((10..=110).contains(&x) && (20..=60).contains(&y), "({}, {})", x, y);
+            let (x, y) = area_point(&circle, &mut rng);
+            assert!(((x - 500) as f64).hypot((y - 500) as f64) <= 50.5, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn centered_spread_favours_the_middle() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut near_middle = |spread| {
+            let area = ClickArea {
+                shape: AreaShape::Rectangle,
+                from: (0, 0),
+                to: (100, 100),
+                spread,
+            };
+            (0..2000)
+                .map(|_| area_point(&area, &mut rng))
+                .filter(|&(x, y)| (25..=75).contains(&x) && (25..=75).contains(&y))
+                .count()
+        };
+        // About a quarter of uniform points, and over half of centered ones.
+        assert!(near_middle(Spread::Uniform) < 700);
+        assert!(near_middle(Spread::Centered) > 1000);
+    }
+}
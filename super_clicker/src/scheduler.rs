@@ -0,0 +1,354 @@
+//! Timetable for scheduled runs.
+//!
+//! The app polls the `Scheduler` once a second; it answers with the clickers
+//! to start or stop. Daily and one-off times are in local time.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use time::{OffsetDateTime, UtcOffset};
+
+use super::settings::{Schedule, ScheduleKind};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The current instant together with the local time zone.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalNow {
+    pub now: SystemTime,
+    /// Local time minus UTC, in seconds, at a given instant. Changes with DST.
+    offset_at: fn(SystemTime) -> i32,
+}
+
+impl LocalNow {
+    pub fn current() -> Self {
+        LocalNow {
+            now: SystemTime::now(),
+            offset_at: system_offset_at,
+        }
+    }
+
+    /// Local time minus UTC, in seconds.
+    pub fn utc_offset_secs(&self) -> i32 {
+        (self.offset_at)(self.now)
+    }
+
+    /// Local time of day at `time`.
+    fn seconds_of_day_at(&self, time: SystemTime) -> u32 {
+        self.local_secs(time).rem_euclid(SECONDS_PER_DAY as i64) as u32
+    }
+
+    /// Seconds since the epoch as the local clock reads them at `time`.
+    fn local_secs(&self, time: SystemTime) -> i64 {
+        unix_secs(time) + (self.offset_at)(time) as i64
+    }
+
+    /// The first instant after `after` when the local clock shows
+    /// `hour:minute`. Worked out per day, so DST changes don't shift it.
+    fn next_local_time(&self, hour: u8, minute: u8, after: SystemTime) -> SystemTime {
+        let target = hour as i64 * 3600 + minute as i64 * 60;
+        let today = self.local_secs(after).div_euclid(SECONDS_PER_DAY as i64);
+        (today..today + 3)
+            .map(|day| {
+                let local = day * SECONDS_PER_DAY as i64 + target;
+                // The offset at the answer may differ from the one at `after`.
+                let guess = local - (self.offset_at)(from_unix_secs(local)) as i64;
+                from_unix_secs(local - (self.offset_at)(from_unix_secs(guess)) as i64)
+            })
+            .find(|&fire| fire > after)
+            .unwrap_or(after + Duration::from_secs(SECONDS_PER_DAY))
+    }
+}
+
+/// The system time zone's offset at `time`, or UTC if it can't be read.
+fn system_offset_at(time: SystemTime) -> i32 {
+    UtcOffset::local_offset_at(OffsetDateTime::from(time)).map_or(0, |offset| offset.whole_seconds())
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    }
+}
+
+fn from_unix_secs(secs: i64) -> SystemTime {
+    if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    }
+}
+
+/// What the app should do for a schedule entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleEvent {
+    /// Start the schedule's clicker (index into `Settings::schedules`).
+    Start(usize),
+    /// The run time is over; stop the clicker.
+    Stop(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+struct RunState {
+    next_fire: Option<SystemTime>,
+    stop_at: Option<SystemTime>,
+}
+
+/// Tracks next-fire and stop times for each entry of `Settings::schedules`.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    runs: Vec<RunState>,
+}
+
+impl Scheduler {
+    pub fn new(schedules: &[Schedule], now: LocalNow) -> Self {
+        let mut scheduler = Scheduler::default();
+        scheduler.reset(schedules, now);
+        scheduler
+    }
+
+    /// Recomputes every next-fire time from `now`.
+    pub fn reset(&mut self, schedules: &[Schedule], now: LocalNow) {
+        self.runs.resize_with(schedules.len(), RunState::default);
+        for (index, schedule) in schedules.iter().enumerate() {
+            self.refresh(index, schedule, now);
+        }
+    }
+
+    /// Recomputes the next-fire time of one entry after it was added, edited
+    /// or toggled. A run in progress keeps its stop time.
+    pub fn refresh(&mut self, index: usize, schedule: &Schedule, now: LocalNow) {
+        if index >= self.runs.len() {
+            self.runs.resize_with(index + 1, RunState::default);
+        }
+        self.runs[index].next_fire = schedule.enabled.then(|| first_fire(&schedule.kind, now));
+    }
+
+    /// Forgets an entry removed from `Settings::schedules`.
+    pub fn remove(&mut self, index: usize) {
+        if index < self.runs.len() {
+            self.runs.remove(index);
+        }
+    }
+
+    /// True when nothing is due to start or stop, so polling can pause.
+    pub fn is_idle(&self) -> bool {
+        self.runs.iter().all(|run| run.next_fire.is_none() && run.stop_at.is_none())
+    }
+
+    /// Returns the starts and stops that are due at `now`.
+    pub fn poll(&mut self, schedules: &[Schedule], now: LocalNow) -> Vec<ScheduleEvent> {
+        let mut events = Vec::new();
+
+        for (index, (run, schedule)) in self.runs.iter_mut().zip(schedules).enumerate() {
+            if run.stop_at.is_some_and(|stop_at| now.now >= stop_at) {
+                run.stop_at = None;
+                events.push(ScheduleEvent::Stop(index));
+            }
+
+            let Some(fire) = run.next_fire else {
+                continue;
+            };
+            if !schedule.enabled || now.now < fire {
+                continue;
+            }
+
+            events.push(ScheduleEvent::Start(index));
+            run.stop_at = (schedule.run_secs > 0).then(|| now.now + Duration::from_secs(schedule.run_secs));
+            // Fires missed while the computer slept are skipped, not caught up one by one.
+            run.next_fire = match schedule.kind {
+                ScheduleKind::Once { .. } => None,
+                ScheduleKind::Daily { hour, minute } => Some(now.next_local_time(hour, minute, now.now)),
+                ScheduleKind::Every { minutes } => {
+                    let mut next = fire + every(minutes);
+                    while next <= now.now {
+                        next += every(minutes);
+                    }
+                    Some(next)
+                }
+            };
+        }
+
+        events
+    }
+
+    pub fn next_fire(&self, index: usize) -> Option<SystemTime> {
+        self.runs.get(index).and_then(|run| run.next_fire)
+    }
+}
+
+fn every(minutes: u32) -> Duration {
+    Duration::from_secs(minutes.max(1) as u64 * 60)
+}
+
+/// First fire time for a schedule enabled at `now`.
+fn first_fire(kind: &ScheduleKind, now: LocalNow) -> SystemTime {
+    match *kind {
+        // A time equal to "now" means the next day; it was set a moment too late.
+        ScheduleKind::Once { hour, minute } | ScheduleKind::Daily { hour, minute } => {
+            now.next_local_time(hour, minute, now.now)
+        }
+        ScheduleKind::Every { minutes } => now.now + every(minutes),
+    }
+}
+
+/// "09:00 (in 2h 5m)" style description of a fire time.
+pub fn describe_fire_time(fire: SystemTime, now: LocalNow) -> String {
+    let seconds = now.seconds_of_day_at(fire);
+    let wait = fire.duration_since(now.now).unwrap_or_default().as_secs();
+    let until = if wait >= 3600 {
+        format!("{}h {}m", wait / 3600, wait % 3600 / 60)
+    } else if wait >= 60 {
+        format!("{}m {}s", wait / 60, wait % 60)
+    } else {
+        format!("{}s", wait)
+    };
+    format!("{:02}:{:02} (in {})", seconds / 3600, seconds % 3600 / 60, until)
+}
+
+/// Parses "HH:MM" (24-hour).
+pub fn parse_time_of_day(value: &str) -> Option<(u8, u8)> {
+    let (hour, minute) = value.trim().split_once(':')?;
+    let hour: u8 = hour.trim().parse().ok()?;
+    let minute: u8 = minute.trim().parse().ok()?;
+    (hour < 24 && minute < 60).then_some((hour, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A UTC midnight.
+    const DAY_ONE: u64 = 11 * SECONDS_PER_DAY;
+
+    fn utc(_: SystemTime) -> i32 {
+        0
+    }
+
+    /// Clocks go forward an hour at 02:00 on the day after `DAY_ONE`.
+    fn spring_forward(time: SystemTime) -> i32 {
+        if unix_secs(time) >= (DAY_ONE + SECONDS_PER_DAY + 2 * 3600) as i64 { 3600 } else { 0 }
+    }
+
+    fn at(seconds_of_day: u32) -> LocalNow {
+        LocalNow {
+            now: UNIX_EPOCH + Duration::from_secs(DAY_ONE + seconds_of_day as u64),
+            offset_at: utc,
+        }
+    }
+
+    fn later(now: LocalNow, seconds: u64) -> LocalNow {
+        LocalNow {
+            now: now.now + Duration::from_secs(seconds),
+            ..now
+        }
+    }
+
+    fn schedule(kind: ScheduleKind, run_secs: u64) -> Schedule {
+        Schedule {
+            clicker_id: 1,
+            clicker: None,
+            kind,
+            run_secs,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn daily_fires_at_next_local_time_and_repeats() {
+        let now = at(8 * 3600 + 30 * 60); // 08:30
+        let schedules = [schedule(ScheduleKind::Daily { hour: 9, minute: 0 }, 0)];
+        let mut scheduler = Scheduler::new(&schedules, now);
+
+        assert_eq!(scheduler.next_fire(0), Some(now.now + Duration::from_secs(30 * 60)));
+        assert!(scheduler.poll(&schedules, later(now, 29 * 60)).is_empty());
+        assert_eq!(scheduler.poll(&schedules, later(now, 30 * 60)), vec![ScheduleEvent::Start(0)]);
+        assert_eq!(
+            scheduler.next_fire(0),
+            Some(now.now + Duration::from_secs(30 * 60 + SECONDS_PER_DAY))
+        );
+    }
+
+    #[test]
+    fn daily_time_already_passed_fires_tomorrow() {
+        let now = at(10 * 3600); // 10:00
+        let schedules = [schedule(ScheduleKind::Daily { hour: 9, minute: 0 }, 0)];
+        let scheduler = Scheduler::new(&schedules, now);
+
+        assert_eq!(scheduler.next_fire(0), Some(now.now + Duration::from_secs(23 * 3600)));
+    }
+
+    #[test]
+    fn every_runs_for_its_duration_then_stops() {
+        let now = at(0);
+        let schedules = [schedule(ScheduleKind::Every { minutes: 15 }, 30)];
+        let mut scheduler = Scheduler::new(&schedules, now);
+
+        let fire = later(now, 15 * 60);
+        assert_eq!(scheduler.poll(&schedules, fire), vec![ScheduleEvent::Start(0)]);
+        assert!(scheduler.poll(&schedules, later(fire, 29)).is_empty());
+        assert_eq!(scheduler.poll(&schedules, later(fire, 30)), vec![ScheduleEvent::Stop(0)]);
+        assert_eq!(scheduler.next_fire(0), Some(fire.now + Duration::from_secs(15 * 60)));
+    }
+
+    #[test]
+    fn missed_fires_after_sleep_start_only_once() {
+        let now = at(0);
+        let schedules = [schedule(ScheduleKind::Every { minutes: 1 }, 30)];
+        let mut scheduler = Scheduler::new(&schedules, now);
+
+        // Asleep for eight hours: one start, not one per missed minute.
+        let woke = later(now, 8 * 3600 + 10);
+        assert_eq!(scheduler.poll(&schedules, woke), vec![ScheduleEvent::Start(0)]);
+        assert!(scheduler.poll(&schedules, later(woke, 1)).is_empty());
+        assert_eq!(scheduler.next_fire(0), Some(now.now + Duration::from_secs(8 * 3600 + 60)));
+        assert_eq!(scheduler.poll(&schedules, later(woke, 30)), vec![ScheduleEvent::Stop(0)]);
+    }
+
+    #[test]
+    fn once_fires_a_single_time() {
+        let now = at(12 * 3600);
+        let schedules = [schedule(ScheduleKind::Once { hour: 12, minute: 1 }, 0)];
+        let mut scheduler = Scheduler::new(&schedules, now);
+
+        assert_eq!(scheduler.poll(&schedules, later(now, 60)), vec![ScheduleEvent::Start(0)]);
+        assert_eq!(scheduler.next_fire(0), None);
+    }
+
+    #[test]
+    fn disabled_entries_never_fire() {
+        let now = at(0);
+        let mut entry = schedule(ScheduleKind::Every { minutes: 1 }, 0);
+        entry.enabled = false;
+        let schedules = [entry];
+        let mut scheduler = Scheduler::new(&schedules, now);
+
+        assert_eq!(scheduler.next_fire(0), None);
+        assert!(scheduler.poll(&schedules, later(now, 3600)).is_empty());
+    }
+
+    #[test]
+    fn daily_keeps_its_local_time_across_dst() {
+        let now = LocalNow {
+            offset_at: spring_forward,
+            ..at(8 * 3600 + 30 * 60)
+        };
+        let schedules = [schedule(ScheduleKind::Daily { hour: 9, minute: 0 }, 0)];
+        let mut scheduler = Scheduler::new(&schedules, now);
+
+        let fire = later(now, 30 * 60);
+        assert_eq!(scheduler.poll(&schedules, fire), vec![ScheduleEvent::Start(0)]);
+        // An hour less than a day later, it is 09:00 local again.
+        let next = scheduler.next_fire(0).unwrap();
+        assert_eq!(next, fire.now + Duration::from_secs(SECONDS_PER_DAY - 3600));
+        assert!(describe_fire_time(next, fire).starts_with("09:00"));
+    }
+
+    #[test]
+    fn parses_time_of_day() {
+        assert_eq!(parse_time_of_day("09:00"), Some((9, 0)));
+        assert_eq!(parse_time_of_day(" 23:59 "), Some((23, 59)));
+        assert_eq!(parse_time_of_day("24:00"), None);
+        assert_eq!(parse_time_of_day("9"), None);
+    }
+}
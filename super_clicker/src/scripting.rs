@@ -0,0 +1,323 @@
+//! Rhai scripts for click logic that a pattern can't express.
+//!
+//! Scripts are `.rhai` files in the `scripts` folder of the config directory.
+//! Besides plain Rhai (variables, loops, branches) they can call
+//! `click(button)`, `press(key)`, `scroll(direction, notches)`,
+//! `move_to(x, y)` and `wait(ms)`. Scripts cannot load modules or use
+//! `eval`, and are interrupted when they exceed their max runtime or the stop
+//! flag is set.
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+use super::clicking::{self, RdevBackend};
+use super::hotkeys;
+use super::keys;
+use super::settings::{Action, MouseButton, ScrollDirection, Settings, SCROLL_NOTCHES};
+
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Longest single sleep inside `wait`, so a stop request is noticed quickly.
+const WAIT_SLICE: Duration = Duration::from_millis(20);
+
+pub fn scripts_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(Settings::config_dir()?.join("scripts"))
+}
+
+/// Names (without extension) of the scripts in the scripts folder, sorted.
+/// Creates the folder so there is somewhere to put the first script.
+pub fn list() -> Vec<String> {
+    let entries = scripts_dir().and_then(|dir| {
+        fs::create_dir_all(&dir)?;
+        Ok(fs::read_dir(dir)?)
+    });
+    let Ok(entries) = entries else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Reads a script given by name (from the scripts folder) or by path.
+pub fn load(name: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let path = Path::new(name);
+    let path = if path.is_file() {
+        path.to_path_buf()
+    } else {
+        scripts_dir()?.join(name).with_extension(SCRIPT_EXTENSION)
+    };
+    Ok(fs::read_to_string(path)?)
+}
+
+/// How a script run ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptOutcome {
+    Finished,
+    TimedOut,
+    Stopped,
+    Failed(String),
+}
+
+impl fmt::Display for ScriptOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptOutcome::Finished => write!(f, "finished"),
+            ScriptOutcome::TimedOut => write!(f, "stopped (max runtime)"),
+            ScriptOutcome::Stopped => write!(f, "stopped"),
+            ScriptOutcome::Failed(error) => write!(f, "failed: {}", error),
+        }
+    }
+}
+
+/// Shared by every host function: tells them when to give up.
+#[derive(Clone)]
+struct Guard {
+    stop: Arc<AtomicBool>,
+    deadline: Instant,
+}
+
+impl Guard {
+    fn interrupted(&self) -> bool {
+        self.stop.load(Ordering::Relaxed) || Instant::now() >= self.deadline
+    }
+
+    fn check(&self) -> Result<(), Box<EvalAltResult>> {
+        if self.interrupted() {
+            Err("script interrupted".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Waits until the CPS cap allows another action, then checks the
+    /// script wasn't stopped meanwhile.
+    fn pace(&self) -> Result<(), Box<EvalAltResult>> {
+        clicking::throttle();
+        self.check()
+    }
+}
+
+/// Runs `source` on the current thread until it ends, `stop` is set, or
+/// `max_runtime` has passed. `actions` counts clicks and key presses.
+pub fn run(source: &str, max_runtime: Duration, stop: Arc<AtomicBool>, actions: Arc<AtomicU64>) -> ScriptOutcome {
+    let guard = Guard {
+        stop,
+        deadline: Instant::now() + max_runtime,
+    };
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_call_levels(64);
+    engine.set_max_string_size(64 * 1024);
+    engine.set_max_array_size(100_000);
+    engine.set_max_map_size(100_000);
+
+    // Catches busy loops that never call a host function.
+    let progress_guard = guard.clone();
+    engine.on_progress(move |_| progress_guard.interrupted().then_some(Dynamic::UNIT));
+
+    let (click_guard, click_actions) = (guard.clone(), Arc::clone(&actions));
+    engine.register_fn("click", move |button: &str| -> Result<(), Box<EvalAltResult>> {
+        let button = match button.to_ascii_lowercase().as_str() {
+            "left" => MouseButton::Left,
+            "middle" => MouseButton::Middle,
+            "right" => MouseButton::Right,
+            _ => return Err(format!("unknown mouse button '{}'", button).into()),
+        };
+        click_guard.check()?;
+        click_guard.pace()?;
+        clicking::perform(&RdevBackend, &Action::Click(button));
+        click_actions.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    });
+
+    let (press_guard, press_actions) = (guard.clone(), Arc::clone(&actions));
+    engine.register_fn("press", move |key: &str| -> Result<(), Box<EvalAltResult>> {
+        let Some(key) = keys::parse_key(key).and_then(keys::key_name) else {
+            return Err(format!("unknown key '{}'", key).into());
+        };
+        press_guard.check()?;
+        press_guard.pace()?;
+        clicking::perform(&RdevBackend, &Action::Press(key.to_string()));
+        press_actions.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    });
+
+    let (scroll_guard, scroll_actions) = (guard.clone(), Arc::clone(&actions));
+    engine.register_fn("scroll", move |direction: &str, notches: i64| -> Result<(), Box<EvalAltResult>> {
+        let Some(direction) = ScrollDirection::from_name(direction) else {
+            return Err(format!("unknown scroll direction '{}'", direction).into());
+        };
+        let Some(notches) = u32::try_from(notches).ok().filter(|notches| SCROLL_NOTCHES.contains(notches)) else {
+            return Err(format!(
+                "notches must be {} to {}, not {}",
+                SCROLL_NOTCHES.start(),
+                SCROLL_NOTCHES.end(),
+                notches
+            )
+            .into());
+        };
+        scroll_guard.check()?;
+        scroll_guard.pace()?;
+        clicking::perform(&RdevBackend, &Action::Scroll(direction, notches));
+        scroll_actions.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    });
+
+    let move_guard = guard.clone();
+    engine.register_fn("move_to", move |x: i64, y: i64| -> Result<(), Box<EvalAltResult>> {
+        move_guard.check()?;
+        clicking::move_to(&RdevBackend, x as i32, y as i32);
+        Ok(())
+    });
+
+    let wait_guard = guard.clone();
+    engine.register_fn("wait", move |ms: i64| -> Result<(), Box<EvalAltResult>> {
+        let until = Instant::now() + Duration::from_millis(ms.max(0) as u64);
+        loop {
+            wait_guard.check()?;
+            let now = Instant::now();
+            if now >= until {
+                return Ok(());
+            }
+            thread::sleep((until - now).min(WAIT_SLICE));
+        }
+    });
+
+    let result = engine.run(source);
+
+    if guard.stop.load(Ordering::Relaxed) {
+        ScriptOutcome::Stopped
+    } else if Instant::now() >= guard.deadline {
+        ScriptOutcome::TimedOut
+    } else {
+        match result {
+            Ok(()) => ScriptOutcome::Finished,
+            Err(error) => ScriptOutcome::Failed(error.to_string()),
+        }
+    }
+}
+
+/// A script running on its own thread, started from the UI.
+pub struct ScriptRun {
+    pub name: String,
+    stop: Arc<AtomicBool>,
+    actions: Arc<AtomicU64>,
+    outcome: Arc<Mutex<Option<ScriptOutcome>>>,
+}
+
+impl ScriptRun {
+    pub fn start(name: String, source: String, max_runtime: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let actions = Arc::new(AtomicU64::new(0));
+        let outcome = Arc::new(Mutex::new(None));
+
+        let (thread_stop, thread_actions, thread_outcome) =
+            (Arc::clone(&stop), Arc::clone(&actions), Arc::clone(&outcome));
+        thread::spawn(move || {
+            let result = run(&source, max_runtime, thread_stop, thread_actions);
+            if let Ok(mut outcome) = thread_outcome.lock() {
+                *outcome = Some(result);
+            }
+        });
+
+        ScriptRun { name, stop, actions, outcome }
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Clicks and key presses sent so far.
+    pub fn actions(&self) -> u64 {
+        self.actions.load(Ordering::Relaxed)
+    }
+
+    /// How the run ended, once it has.
+    pub fn outcome(&self) -> Option<ScriptOutcome> {
+        self.outcome.lock().ok().and_then(|outcome| outcome.clone())
+    }
+}
+
+/// Runs a script without opening a window (`--run-script <name>`). Returns
+/// the process exit code.
+pub fn run_from_cli(name: &str) -> i32 {
+    let settings = Settings::load().unwrap_or_default();
+    let source = match load(name) {
+        Ok(source) => source,
+        Err(error) => {
+            error!(script = %name, %error, "could not load script");
+            return 2;
+        }
+    };
+
+    let stop = Arc::new(AtomicBool::new(false));
+    hotkeys::set_fail_safe(settings.fail_safe);
+    clicking::set_max_cps(settings.rate_limit.max_cps);
+    hotkeys::spawn_script_stop_listener(&settings.scripts.stop_hotkey, Arc::clone(&stop));
+
+    let max_runtime = Duration::from_secs(settings.scripts.max_runtime_secs);
+    let outcome = run(&source, max_runtime, stop, Arc::new(AtomicU64::new(0)));
+    info!(script = %name, %outcome, "script ended");
+    match outcome {
+        ScriptOutcome::Finished => 0,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_for(source: &str, max_runtime: Duration, stop: bool) -> ScriptOutcome {
+        run(
+            source,
+            max_runtime,
+            Arc::new(AtomicBool::new(stop)),
+            Arc::new(AtomicU64::new(0)),
+        )
+    }
+
+    #[test]
+    fn busy_loop_is_stopped_at_max_runtime() {
+        let outcome = run_for("let n = 0; loop { n += 1; }", Duration::from_millis(100), false);
+        assert_eq!(outcome, ScriptOutcome::TimedOut);
+    }
+
+    #[test]
+    fn stop_flag_interrupts_wait() {
+        let started = Instant::now();
+        let outcome = run_for("wait(10000);", Duration::from_secs(60), true);
+        assert_eq!(outcome, ScriptOutcome::Stopped);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        assert!(matches!(run_for("let x = ;", Duration::from_secs(1), false), ScriptOutcome::Failed(_)));
+        assert!(matches!(run_for(r#"click("sideways");"#, Duration::from_secs(1), false), ScriptOutcome::Failed(_)));
+        assert!(matches!(run_for(r#"scroll("sideways", 1);"#, Duration::from_secs(1), false), ScriptOutcome::Failed(_)));
+        assert!(matches!(run_for(r#"import "other";"#, Duration::from_secs(1), false), ScriptOutcome::Failed(_)));
+    }
+
+    #[test]
+    fn plain_logic_finishes() {
+        let source = "let total = 0; for i in 0..10 { if i % 2 == 0 { total += i; } } wait(1);";
+        assert_eq!(run_for(source, Duration::from_secs(5), false), ScriptOutcome::Finished);
+    }
+}
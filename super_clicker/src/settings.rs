@@ -1,15 +1,807 @@
 use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
 use std::fs;
-use std::path::PathBuf;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::{self, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::keys;
+
+/// Environment variable that moves the config directory, like `--config-dir`.
+pub const CONFIG_DIR_ENV: &str = "SUPER_CLICKER_CONFIG_DIR";
+
+/// A file of this name next to the executable turns on portable mode: the
+/// config goes in a `config` folder beside it.
+pub const PORTABLE_MARKER: &str = "portable";
+
+/// Set from the `--config-dir` command line flag.
+static CONFIG_DIR_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Where the config directory comes from, strongest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Flag,
+    Environment,
+    Portable,
+    System,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Flag => write!(f, "--config-dir"),
+            ConfigSource::Environment => write!(f, "{}", CONFIG_DIR_ENV),
+            ConfigSource::Portable => write!(f, "portable mode"),
+            ConfigSource::System => write!(f, "system default"),
+        }
+    }
+}
+
+/// Moves the config directory for the rest of the process (`--config-dir`).
+/// Relative paths are taken from the current directory.
+pub fn set_config_dir(dir: PathBuf) {
+    let dir = path::absolute(&dir).unwrap_or(dir);
+    if let Ok(mut config_dir) = CONFIG_DIR_OVERRIDE.lock() {
+        *config_dir = Some(dir);
+    }
+}
+
+/// The `config` folder next to the executable, if the portable marker is there.
+fn portable_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    dir.join(PORTABLE_MARKER).is_file().then(|| dir.join("config"))
+}
+
+/// Picks the config directory from the flag, the environment variable or
+/// portable mode, in that order. `None` means the system default.
+fn resolve_config_dir(
+    flag: Option<PathBuf>,
+    env: Option<OsString>,
+    portable: Option<PathBuf>,
+) -> Option<(PathBuf, ConfigSource)> {
+    let env = env.filter(|value| !value.is_empty()).map(PathBuf::from);
+    flag.map(|dir| (dir, ConfigSource::Flag))
+        .or_else(|| env.map(|dir| (path::absolute(&dir).unwrap_or(dir), ConfigSource::Environment)))
+        .or_else(|| portable.map(|dir| (dir, ConfigSource::Portable)))
+}
+
+/// How a single wheel notch or arrow key press changes the click interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IntervalStepMode {
+    /// Every step adds or removes a fixed number of milliseconds.
+    #[default]
+    Linear,
+    /// Every step scales the interval by a fixed percentage, so the same
+    /// gesture is equally useful at 5 ms and at 5 s.
+    Proportional,
+}
+
+impl IntervalStepMode {
+    pub const ALL: [IntervalStepMode; 2] = [IntervalStepMode::Linear, IntervalStepMode::Proportional];
+}
+
+impl fmt::Display for IntervalStepMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalStepMode::Linear => write!(f, "Linear (1 ms)"),
+            IntervalStepMode::Proportional => write!(f, "Proportional (%)"),
+        }
+    }
+}
+
+/// Which color theme the UI uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThemePreference {
+    /// Follow the operating system's light/dark setting.
+    #[default]
+    System,
+    Light,
+    Dark,
+    HighContrast,
+    /// Use the palette from `palette.json` in the config directory.
+    Custom,
+}
+
+impl ThemePreference {
+    pub const ALL: [ThemePreference; 5] = [
+        ThemePreference::System,
+        ThemePreference::Light,
+        ThemePreference::Dark,
+        ThemePreference::HighContrast,
+        ThemePreference::Custom,
+    ];
+}
+
+impl fmt::Display for ThemePreference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemePreference::System => write!(f, "System"),
+            ThemePreference::Light => write!(f, "Light"),
+            ThemePreference::Dark => write!(f, "Dark"),
+            ThemePreference::HighContrast => write!(f, "High Contrast"),
+            ThemePreference::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Mouse button a clicker presses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Middle,
+    Right,
+}
+
+impl MouseButton {
+    /// Parses the legacy `mouse_button` setting ("Left", "Middle", "Right").
+    fn from_name(name: &str) -> Self {
+        match name {
+            "Right" => MouseButton::Right,
+            "Middle" => MouseButton::Middle,
+            _ => MouseButton::Left,
+        }
+    }
+}
+
+/// Direction of a scroll action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ScrollDirection {
+    /// Parses "up", "down", "left" or "right", ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "up" => Some(ScrollDirection::Up),
+            "down" => Some(ScrollDirection::Down),
+            "left" => Some(ScrollDirection::Left),
+            "right" => Some(ScrollDirection::Right),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name, as used in macros.
+    pub fn name(self) -> &'static str {
+        match self {
+            ScrollDirection::Up => "up",
+            ScrollDirection::Down => "down",
+            ScrollDirection::Left => "left",
+            ScrollDirection::Right => "right",
+        }
+    }
+}
+
+/// What a clicker does on every tick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Click(MouseButton),
+    /// Press and release a key, by name (see `keys::parse_key`).
+    Press(String),
+    /// Turn the scroll wheel by a number of notches.
+    Scroll(ScrollDirection, u32),
+}
+
+/// Notches one scroll may turn. rdev sends notches × 120 as an `i16` on
+/// Windows, so 273 is the most that fits.
+pub const SCROLL_NOTCHES: RangeInclusive<u32> = 1..=273;
+
+impl Default for Action {
+    fn default() -> Self {
+        Action::Click(MouseButton::Left)
+    }
+}
+
+/// The choices offered by the action picker. `Key` keeps the clicker's
+/// current key name when switching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    LeftClick,
+    MiddleClick,
+    RightClick,
+    KeyPress,
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 8] = [
+        ActionKind::LeftClick,
+        ActionKind::MiddleClick,
+        ActionKind::RightClick,
+        ActionKind::KeyPress,
+        ActionKind::ScrollUp,
+        ActionKind::ScrollDown,
+        ActionKind::ScrollLeft,
+        ActionKind::ScrollRight,
+    ];
+}
+
+impl fmt::Display for ActionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionKind::LeftClick => write!(f, "Left Click"),
+            ActionKind::MiddleClick => write!(f, "Middle Click"),
+            ActionKind::RightClick => write!(f, "Right Click"),
+            ActionKind::KeyPress => write!(f, "Key Press"),
+            ActionKind::ScrollUp => write!(f, "Scroll Up"),
+            ActionKind::ScrollDown => write!(f, "Scroll Down"),
+            ActionKind::ScrollLeft => write!(f, "Scroll Left"),
+            ActionKind::ScrollRight => write!(f, "Scroll Right"),
+        }
+    }
+}
+
+impl Action {
+    pub fn kind(&self) -> ActionKind {
+        match self {
+            Action::Click(MouseButton::Left) => ActionKind::LeftClick,
+            Action::Click(MouseButton::Middle) => ActionKind::MiddleClick,
+            Action::Click(MouseButton::Right) => ActionKind::RightClick,
+            Action::Press(_) => ActionKind::KeyPress,
+            Action::Scroll(ScrollDirection::Up, _) => ActionKind::ScrollUp,
+            Action::Scroll(ScrollDirection::Down, _) => ActionKind::ScrollDown,
+            Action::Scroll(ScrollDirection::Left, _) => ActionKind::ScrollLeft,
+            Action::Scroll(ScrollDirection::Right, _) => ActionKind::ScrollRight,
+        }
+    }
+
+    /// Converts to `kind`, keeping the key name when it stays a key press and
+    /// the notches when it stays a scroll.
+    pub fn with_kind(&self, kind: ActionKind) -> Action {
+        let notches = match self {
+            Action::Scroll(_, notches) => *notches,
+            _ => 1,
+        };
+        match (kind, self) {
+            (ActionKind::LeftClick, _) => Action::Click(MouseButton::Left),
+            (ActionKind::MiddleClick, _) => Action::Click(MouseButton::Middle),
+            (ActionKind::RightClick, _) => Action::Click(MouseButton::Right),
+            (ActionKind::KeyPress, Action::Press(key)) => Action::Press(key.clone()),
+            (ActionKind::KeyPress, _) => Action::Press(String::from("Space")),
+            (ActionKind::ScrollUp, _) => Action::Scroll(ScrollDirection::Up, notches),
+            (ActionKind::ScrollDown, _) => Action::Scroll(ScrollDirection::Down, notches),
+            (ActionKind::ScrollLeft, _) => Action::Scroll(ScrollDirection::Left, notches),
+            (ActionKind::ScrollRight, _) => Action::Scroll(ScrollDirection::Right, notches),
+        }
+    }
+}
+
+/// One step of a clicker's repeating pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternStep {
+    Act(Action),
+    /// Move the cursor to a screen position.
+    MoveTo(i32, i32),
+    /// Pause in milliseconds, measured from the start of the previous step.
+    Wait(u64),
+}
+
+/// The choices offered by the pattern step picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    Action(ActionKind),
+    Move,
+    Wait,
+}
+
+impl StepKind {
+    pub const ALL: [StepKind; 10] = [
+        StepKind::Action(ActionKind::LeftClick),
+        StepKind::Action(ActionKind::MiddleClick),
+        StepKind::Action(ActionKind::RightClick),
+        StepKind::Action(ActionKind::KeyPress),
+        StepKind::Action(ActionKind::ScrollUp),
+        StepKind::Action(ActionKind::ScrollDown),
+        StepKind::Action(ActionKind::ScrollLeft),
+        StepKind::Action(ActionKind::ScrollRight),
+        StepKind::Move,
+        StepKind::Wait,
+    ];
+}
+
+impl fmt::Display for StepKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepKind::Action(kind) => write!(f, "{}", kind),
+            StepKind::Move => write!(f, "Move (x, y)"),
+            StepKind::Wait => write!(f, "Wait (ms)"),
+        }
+    }
+}
+
+impl PatternStep {
+    pub fn kind(&self) -> StepKind {
+        match self {
+            PatternStep::Act(action) => StepKind::Action(action.kind()),
+            PatternStep::MoveTo(..) => StepKind::Move,
+            PatternStep::Wait(_) => StepKind::Wait,
+        }
+    }
+
+    /// Converts to `kind`, keeping the key name when it stays a key press.
+    pub fn with_kind(&self, kind: StepKind) -> PatternStep {
+        match (kind, self) {
+            (StepKind::Wait, PatternStep::Wait(ms)) => PatternStep::Wait(*ms),
+            (StepKind::Wait, _) => PatternStep::Wait(100),
+            (StepKind::Move, PatternStep::MoveTo(x, y)) => PatternStep::MoveTo(*x, *y),
+            (StepKind::Move, _) => PatternStep::MoveTo(0, 0),
+            (StepKind::Action(kind), PatternStep::Act(action)) => PatternStep::Act(action.with_kind(kind)),
+            (StepKind::Action(kind), _) => PatternStep::Act(Action::default().with_kind(kind)),
+        }
+    }
+}
+
+/// Shape of a click area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AreaShape {
+    #[default]
+    Rectangle,
+    Circle,
+}
+
+impl AreaShape {
+    pub const ALL: [AreaShape; 2] = [AreaShape::Rectangle, AreaShape::Circle];
+}
+
+impl fmt::Display for AreaShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AreaShape::Rectangle => write!(f, "Rectangle"),
+            AreaShape::Circle => write!(f, "Circle"),
+        }
+    }
+}
+
+/// How random points spread over a click area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Spread {
+    /// Every point equally likely.
+    #[default]
+    Uniform,
+    /// More likely near the middle, like a person aiming.
+    Centered,
+}
+
+impl Spread {
+    pub const ALL: [Spread; 2] = [Spread::Uniform, Spread::Centered];
+}
+
+impl fmt::Display for Spread {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Spread::Uniform => write!(f, "Uniform"),
+            Spread::Centered => write!(f, "Centered"),
+        }
+    }
+}
+
+/// A screen region that clicks land anywhere inside. Given by two points:
+/// opposite corners of a rectangle, or a circle's center and a point on
+/// its edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickArea {
+    pub shape: AreaShape,
+    pub from: (i32, i32),
+    pub to: (i32, i32),
+    pub spread: Spread,
+}
+
+impl ClickArea {
+    /// Converts to `shape`, covering about the same region: a circle fits
+    /// inside the rectangle, a rectangle goes round the circle.
+    pub fn with_shape(&self, shape: AreaShape) -> ClickArea {
+        let (from, to) = match (self.shape, shape) {
+            (AreaShape::Rectangle, AreaShape::Circle) => {
+                let center = ((self.from.0 + self.to.0) / 2, (self.from.1 + self.to.1) / 2);
+                let radius = (self.from.0 - self.to.0).abs().min((self.from.1 - self.to.1).abs()) / 2;
+                (center, (center.0 + radius, center.1))
+            }
+            (AreaShape::Circle, AreaShape::Rectangle) => {
+                let (dx, dy) = ((self.to.0 - self.from.0) as f64, (self.to.1 - self.from.1) as f64);
+                let radius = dx.hypot(dy).round() as i32;
+                (
+                    (self.from.0 - radius, self.from.1 - radius),
+                    (self.from.0 + radius, self.from.1 + radius),
+                )
+            }
+            _ => (self.from, self.to),
+        };
+        ClickArea { shape, from, to, ..*self }
+    }
+}
+
+/// How a clicker moves the cursor between actions.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Motion {
+    /// Leave the cursor alone.
+    #[default]
+    Still,
+    /// Nudge the cursor by `pixels` after every action, round a small square.
+    Jiggle { pixels: u32 },
+    /// Follow the points as a closed loop at `speed` pixels per second.
+    Path { points: Vec<(i32, i32)>, speed: u32 },
+    /// Go round a circle at `speed` pixels per second.
+    Circle { center: (i32, i32), radius: u32, speed: u32 },
+}
+
+/// The choices offered by the movement picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionKind {
+    Still,
+    Jiggle,
+    Path,
+    Circle,
+}
+
+impl MotionKind {
+    pub const ALL: [MotionKind; 4] = [MotionKind::Still, MotionKind::Jiggle, MotionKind::Path, MotionKind::Circle];
+}
+
+impl fmt::Display for MotionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MotionKind::Still => write!(f, "Still"),
+            MotionKind::Jiggle => write!(f, "Jiggle"),
+            MotionKind::Path => write!(f, "Path"),
+            MotionKind::Circle => write!(f, "Circle"),
+        }
+    }
+}
+
+impl Motion {
+    pub fn kind(&self) -> MotionKind {
+        match self {
+            Motion::Still => MotionKind::Still,
+            Motion::Jiggle { .. } => MotionKind::Jiggle,
+            Motion::Path { .. } => MotionKind::Path,
+            Motion::Circle { .. } => MotionKind::Circle,
+        }
+    }
+
+    /// Converts to `kind`, keeping the speed when switching between paths.
+    pub fn with_kind(&self, kind: MotionKind) -> Motion {
+        let speed = match self {
+            Motion::Path { speed, .. } | Motion::Circle { speed, .. } => *speed,
+            _ => 300,
+        };
+        match (kind, self) {
+            (MotionKind::Still, _) => Motion::Still,
+            (MotionKind::Jiggle, Motion::Jiggle { pixels }) => Motion::Jiggle { pixels: *pixels },
+            (MotionKind::Jiggle, _) => Motion::Jiggle { pixels: 3 },
+            (MotionKind::Path, Motion::Path { points, .. }) => Motion::Path { points: points.clone(), speed },
+            (MotionKind::Path, _) => Motion::Path { points: Vec::new(), speed },
+            (MotionKind::Circle, Motion::Circle { center, radius, .. }) => Motion::Circle {
+                center: *center,
+                radius: *radius,
+                speed,
+            },
+            (MotionKind::Circle, _) => Motion::Circle {
+                center: (500, 500),
+                radius: 100,
+                speed,
+            },
+        }
+    }
+}
+
+/// A global key combination.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Key name; `None` disables the hotkey.
+    pub key: Option<String>,
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(key) = &self.key else {
+            return write!(f, "None");
+        };
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", key)
+    }
+}
+
+/// One independent clicker: its own action, timing, target and toggle hotkey.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickerConfig {
+    /// Stable identity for schedules, which outlives renames. 0 until assigned.
+    pub id: u32,
+    pub name: String,
+    pub action: Action,
+    pub interval_ms: u64,
+    /// Screen position to click at; `None` clicks wherever the cursor is.
+    pub target: Option<(i32, i32)>,
+    /// Clicks land at a random point in here instead of `target`.
+    pub area: Option<ClickArea>,
+    pub hotkey: Hotkey,
+    /// Steps repeated in a loop instead of `action` every `interval_ms`.
+    /// Empty, or without any action step, means the plain action.
+    pub pattern: Vec<PatternStep>,
+    /// How the cursor moves between actions.
+    pub motion: Motion,
+    /// Time to glide to `target` or a pattern's move step along a curve.
+    /// 0 jumps straight there.
+    pub glide_ms: u64,
+}
+
+impl Default for ClickerConfig {
+    fn default() -> Self {
+        ClickerConfig {
+            id: 0,
+            name: String::from("Clicker"),
+            action: Action::default(),
+            interval_ms: 100,
+            target: None,
+            area: None,
+            hotkey: Hotkey::default(),
+            pattern: Vec::new(),
+            motion: Motion::default(),
+            glide_ms: 0,
+        }
+    }
+}
+
+impl ClickerConfig {
+    /// A random non-zero id for which `in_use` is false.
+    pub fn new_id(in_use: impl Fn(u32) -> bool) -> u32 {
+        loop {
+            let id = rand::random::<u32>();
+            if id != 0 && !in_use(id) {
+                return id;
+            }
+        }
+    }
+}
+
+/// When a scheduled run fires. Times of day are local.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleKind {
+    /// Fires once at the next occurrence of this time, then disables itself.
+    Once { hour: u8, minute: u8 },
+    Daily { hour: u8, minute: u8 },
+    /// Fires every `minutes`, counted from when the schedule was enabled.
+    Every { minutes: u32 },
+}
+
+impl fmt::Display for ScheduleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleKind::Once { hour, minute } => write!(f, "Once at {:02}:{:02}", hour, minute),
+            ScheduleKind::Daily { hour, minute } => write!(f, "Every day at {:02}:{:02}", hour, minute),
+            ScheduleKind::Every { minutes } => write!(f, "Every {} min", minutes),
+        }
+    }
+}
+
+/// The choices offered by the schedule picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScheduleRepeat {
+    Once,
+    #[default]
+    Daily,
+    Every,
+}
+
+impl ScheduleRepeat {
+    pub const ALL: [ScheduleRepeat; 3] = [ScheduleRepeat::Once, ScheduleRepeat::Daily, ScheduleRepeat::Every];
+}
+
+impl fmt::Display for ScheduleRepeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleRepeat::Once => write!(f, "Once at"),
+            ScheduleRepeat::Daily => write!(f, "Daily at"),
+            ScheduleRepeat::Every => write!(f, "Every (min)"),
+        }
+    }
+}
+
+/// A timetable entry that starts one of the clickers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// `ClickerConfig::id` of the clicker to start.
+    #[serde(default)]
+    pub clicker_id: u32,
+    /// Clicker name from files written before clickers had ids; replaced by
+    /// `clicker_id` on load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clicker: Option<String>,
+    pub kind: ScheduleKind,
+    /// How long each run lasts; 0 runs until stopped by hand.
+    pub run_secs: u64,
+    pub enabled: bool,
+}
+
+/// Emergency stop triggers watched by the global input listener. Any of them
+/// stops every running engine, whatever the hotkey state.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailSafeSettings {
+    /// Stop when the cursor is flung into a corner of the primary screen.
+    pub corner_enabled: bool,
+    /// How close (in pixels) to the corner counts as "in the corner".
+    pub corner_margin_px: u32,
+    /// Stop when the mouse is moved quickly by the user.
+    pub movement_enabled: bool,
+    /// Distance in pixels the cursor must travel within `movement_window_ms`.
+    pub movement_threshold_px: u32,
+    pub movement_window_ms: u64,
+    /// Stop when Escape is pressed `escape_presses` times in a row.
+    pub escape_enabled: bool,
+    pub escape_presses: u32,
+}
+
+impl Default for FailSafeSettings {
+    fn default() -> Self {
+        FailSafeSettings {
+            corner_enabled: true,
+            corner_margin_px: 2,
+            movement_enabled: false,
+            movement_threshold_px: 300,
+            movement_window_ms: 200,
+            escape_enabled: true,
+            escape_presses: 3,
+        }
+    }
+}
+
+/// Least severe diagnostics written to the log (see `logging`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogLevel::Error => write!(f, "Error"),
+            LogLevel::Warn => write!(f, "Warning"),
+            LogLevel::Info => write!(f, "Info"),
+            LogLevel::Debug => write!(f, "Debug"),
+            LogLevel::Trace => write!(f, "Trace"),
+        }
+    }
+}
+
+/// When to stop everything without being asked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoStopSettings {
+    /// Stop after this long without keyboard or mouse input from the user. 0 disables it.
+    pub idle_secs: u64,
+    /// Stop when the computer is locked.
+    pub stop_on_lock: bool,
+}
+
+impl Default for AutoStopSettings {
+    fn default() -> Self {
+        AutoStopSettings {
+            idle_secs: 0,
+            stop_on_lock: true,
+        }
+    }
+}
+
+/// Protection against runaway click rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitSettings {
+    /// Hard cap on clicks per second for all clickers and scripts together,
+    /// enforced by the engine however the intervals were set. 0 disables the cap.
+    pub max_cps: u32,
+    /// Going below this interval asks for confirmation once.
+    pub warn_below_ms: u64,
+    /// The user confirmed a low interval; don't ask again.
+    pub low_interval_confirmed: bool,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        RateLimitSettings {
+            max_cps: 100,
+            warn_below_ms: 20,
+            low_interval_confirmed: false,
+        }
+    }
+}
+
+/// Limits and controls for Rhai scripts (see `scripting`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptSettings {
+    /// A script still running after this long is stopped.
+    pub max_runtime_secs: u64,
+    /// Global hotkey that stops the running script.
+    pub stop_hotkey: Hotkey,
+}
+
+impl Default for ScriptSettings {
+    fn default() -> Self {
+        ScriptSettings {
+            max_runtime_secs: 600,
+            stop_hotkey: Hotkey {
+                ctrl: true,
+                alt: true,
+                shift: false,
+                key: Some(String::from("F8")),
+            },
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
+    pub clickers: Vec<ClickerConfig>,
+    // Legacy single-clicker fields. Only read to build the first clicker when
+    // `clickers` is empty, and no longer written.
+    #[serde(skip_serializing)]
     pub interval_ms: u64,
+    #[serde(skip_serializing)]
     pub mouse_button: String,
     pub enable_dynamic_adjustment: bool,
+    pub interval_step_mode: IntervalStepMode,
+    pub theme_preference: ThemePreference,
+    /// Last screen position (x, y) of the compact mini-mode window.
+    pub mini_mode_position: Option<(i32, i32)>,
+    pub fail_safe: FailSafeSettings,
+    pub rate_limit: RateLimitSettings,
+    pub auto_stop: AutoStopSettings,
+    /// Countdown before clicking starts, so the first click doesn't land on our own window.
+    pub start_delay_secs: u32,
+    /// Play a tick sound every second of the countdown.
+    pub start_delay_tick: bool,
+    pub schedules: Vec<Schedule>,
+    pub scripts: ScriptSettings,
+    pub log_level: LogLevel,
+    /// Measured sleep overshoot; engines wake this much early (see `benchmark`).
+    pub timing_offset_us: u64,
+    #[serde(skip_serializing)]
     pub toggle_hotkey_ctrl: bool,
+    #[serde(skip_serializing)]
     pub toggle_hotkey_alt: bool,
+    #[serde(skip_serializing)]
     pub toggle_hotkey_shift: bool,
+    #[serde(skip_serializing)]
     pub toggle_hotkey_key: Option<String>,
     pub interval_hotkey_ctrl: bool,
     pub interval_hotkey_alt: bool,
@@ -20,9 +812,22 @@ pub struct Settings {
 impl Default for Settings {
     fn default() -> Self {
         Settings {
+            clickers: Vec::new(),
             interval_ms: 100,
             mouse_button: String::from("Left"),
             enable_dynamic_adjustment: true,
+            interval_step_mode: IntervalStepMode::Linear,
+            theme_preference: ThemePreference::System,
+            mini_mode_position: None,
+            fail_safe: FailSafeSettings::default(),
+            rate_limit: RateLimitSettings::default(),
+            auto_stop: AutoStopSettings::default(),
+            start_delay_secs: 0,
+            start_delay_tick: false,
+            schedules: Vec::new(),
+            scripts: ScriptSettings::default(),
+            log_level: LogLevel::Info,
+            timing_offset_us: 0,
             toggle_hotkey_ctrl: true,
             toggle_hotkey_alt: true,
             toggle_hotkey_shift: false,
@@ -38,12 +843,106 @@ impl Default for Settings {
 impl Settings {
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let config_path = Self::config_path()?;
-        if config_path.exists() {
+        let mut settings = if config_path.exists() {
             let content = fs::read_to_string(&config_path)?;
-            let settings = serde_json::from_str(&content)?;
-            Ok(settings)
+            serde_json::from_str(&content)?
         } else {
-            Ok(Settings::default())
+            Settings::default()
+        };
+        settings.migrate_legacy_clicker();
+        settings.assign_clicker_ids();
+        Ok(settings)
+    }
+
+    /// Reads `settings.json` again after it was edited outside the app, and
+    /// rejects content the app could not run with.
+    pub fn reload() -> Result<Self, Box<dyn std::error::Error>> {
+        let settings = Self::load()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Checks what the file format alone can't: intervals above zero and
+    /// key names we know.
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let known = |key: &Option<String>| key.as_deref().is_none_or(|key| keys::parse_key(key).is_some());
+        for clicker in &self.clickers {
+            if clicker.interval_ms == 0 {
+                return Err(format!("{}: interval must be above 0 ms", clicker.name).into());
+            }
+            if !known(&clicker.hotkey.key) {
+                return Err(format!("{}: unknown hotkey key {}", clicker.name, clicker.hotkey).into());
+            }
+            let actions = clicker.pattern.iter().filter_map(|step| match step {
+                PatternStep::Act(action) => Some(action),
+                _ => None,
+            });
+            for action in std::iter::once(&clicker.action).chain(actions) {
+                match action {
+                    Action::Press(key) if keys::parse_key(key).is_none() => {
+                        return Err(format!("{}: unknown key {}", clicker.name, key).into());
+                    }
+                    Action::Scroll(_, notches) if !SCROLL_NOTCHES.contains(notches) => {
+                        return Err(format!(
+                            "{}: scroll notches must be {} to {}, not {}",
+                            clicker.name,
+                            SCROLL_NOTCHES.start(),
+                            SCROLL_NOTCHES.end(),
+                            notches
+                        )
+                        .into());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !known(&self.scripts.stop_hotkey.key) {
+            return Err(format!("unknown script stop key {}", self.scripts.stop_hotkey).into());
+        }
+        Ok(())
+    }
+
+    /// Last modification time of `settings.json`, used to pick up outside edits.
+    pub fn modified() -> Option<SystemTime> {
+        fs::metadata(Self::config_path().ok()?).and_then(|meta| meta.modified()).ok()
+    }
+
+    /// Builds the first clicker from the pre-multi-clicker fields, so older
+    /// settings files keep their interval, button and toggle hotkey.
+    pub fn migrate_legacy_clicker(&mut self) {
+        if !self.clickers.is_empty() {
+            return;
+        }
+        self.clickers.push(ClickerConfig {
+            name: String::from("Clicker 1"),
+            action: Action::Click(MouseButton::from_name(&self.mouse_button)),
+            interval_ms: self.interval_ms,
+            target: None,
+            hotkey: Hotkey {
+                ctrl: self.toggle_hotkey_ctrl,
+                alt: self.toggle_hotkey_alt,
+                shift: self.toggle_hotkey_shift,
+                key: self.toggle_hotkey_key.clone(),
+            },
+            ..ClickerConfig::default()
+        });
+    }
+
+    /// Gives clickers without an id, or with one already taken, a fresh id,
+    /// and points schedules from older files at their clicker by name.
+    pub fn assign_clicker_ids(&mut self) {
+        for index in 0..self.clickers.len() {
+            let id = self.clickers[index].id;
+            if id == 0 || self.clickers[..index].iter().any(|clicker| clicker.id == id) {
+                self.clickers[index].id = ClickerConfig::new_id(|id| self.clickers.iter().any(|clicker| clicker.id == id));
+            }
+        }
+        for schedule in &mut self.schedules {
+            if let Some(name) = schedule.clicker.take()
+                && let Some(clicker) = self.clickers.iter().find(|clicker| clicker.name == name)
+            {
+                schedule.clicker_id = clicker.id;
+            }
         }
     }
 
@@ -57,8 +956,97 @@ impl Settings {
         Ok(())
     }
 
-    fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    /// Directory holding `settings.json` and the other user files (palette, ...).
+    pub fn config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Self::config_location()?.0)
+    }
+
+    /// The config directory and what chose it.
+    pub fn config_location() -> Result<(PathBuf, ConfigSource), Box<dyn std::error::Error>> {
+        let flag = CONFIG_DIR_OVERRIDE.lock().ok().and_then(|dir| dir.clone());
+        if let Some(location) = resolve_config_dir(flag, std::env::var_os(CONFIG_DIR_ENV), portable_dir()) {
+            return Ok(location);
+        }
         let config_dir = dirs::config_dir().ok_or("Could not determine config directory")?;
-        Ok(config_dir.join("super_clicker").join("settings.json"))
+        Ok((config_dir.join("super_clicker"), ConfigSource::System))
+    }
+
+    /// Directory for records the app writes on its own (session history).
+    /// Follows the config directory when that was moved, so isolated copies
+    /// don't share a history.
+    pub fn data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        match Self::config_location()? {
+            (_, ConfigSource::System) => {
+                let data_dir = dirs::data_dir().ok_or("Could not determine data directory")?;
+                Ok(data_dir.join("super_clicker"))
+            }
+            (dir, _) => Ok(dir),
+        }
+    }
+
+    fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Self::config_dir()?.join("settings.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_beats_environment_beats_portable() {
+        let flag = Some(PathBuf::from("/flag"));
+        let env = Some(OsString::from("/env"));
+        let portable = Some(PathBuf::from("/app/config"));
+
+        let resolve = |flag: &Option<PathBuf>, env: &Option<OsString>, portable: &Option<PathBuf>| {
+            resolve_config_dir(flag.clone(), env.clone(), portable.clone()).map(|(_, source)| source)
+        };
+        assert_eq!(resolve(&flag, &env, &portable), Some(ConfigSource::Flag));
+        assert_eq!(resolve(&None, &env, &portable), Some(ConfigSource::Environment));
+        assert_eq!(resolve(&None, &Some(OsString::new()), &portable), Some(ConfigSource::Portable));
+        assert_eq!(resolve(&None, &None, &None), None);
+    }
+
+    #[test]
+    fn clicker_ids_are_unique_and_old_schedules_find_theirs() {
+        let mut settings: Settings = serde_json::from_str(
+            r#"{
+                "clickers": [{"name": "Fast"}, {"name": "Slow"}],
+                "schedules": [{"clicker": "Slow", "kind": {"Every": {"minutes": 5}}, "run_secs": 0, "enabled": true}]
+            }"#,
+        )
+        .unwrap();
+        settings.clickers.push(settings.clickers[0].clone());
+        settings.assign_clicker_ids();
+
+        let ids: Vec<u32> = settings.clickers.iter().map(|clicker| clicker.id).collect();
+        assert!(ids.iter().all(|id| *id != 0));
+        assert!(ids[0] != ids[1] && ids[1] != ids[2] && ids[0] != ids[2]);
+        assert_eq!(settings.schedules[0].clicker_id, ids[1]);
+        assert_eq!(settings.schedules[0].clicker, None);
+    }
+
+    #[test]
+    fn validate_rejects_zero_intervals_and_unknown_keys() {
+        let mut settings = Settings::default();
+        settings.migrate_legacy_clicker();
+        assert!(settings.validate().is_ok());
+
+        settings.clickers[0].action = Action::Press(String::from("Enterr"));
+        assert!(settings.validate().unwrap_err().to_string().contains("Enterr"));
+        settings.clickers[0].action = Action::Press(String::from("enter"));
+        assert!(settings.validate().is_ok());
+
+        settings.scripts.stop_hotkey.key = Some(String::from("F99"));
+        assert!(settings.validate().is_err());
+        settings.scripts.stop_hotkey.key = None;
+
+        settings.clickers[0].pattern = vec![PatternStep::Act(Action::Scroll(ScrollDirection::Up, 274))];
+        assert!(settings.validate().unwrap_err().to_string().contains("274"));
+        settings.clickers[0].pattern.clear();
+
+        settings.clickers[0].interval_ms = 0;
+        assert!(settings.validate().is_err());
     }
 }
@@ -0,0 +1,12 @@
+//! Short audible cues.
+
+/// Plays the countdown tick. Silently does nothing where unsupported.
+pub fn tick() {
+    #[cfg(windows)]
+    unsafe {
+        // MB_OK is the default system sound; MessageBeep returns immediately.
+        windows_sys::Win32::UI::WindowsAndMessaging::MessageBeep(
+            windows_sys::Win32::UI::WindowsAndMessaging::MB_OK,
+        );
+    }
+}
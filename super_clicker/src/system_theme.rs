@@ -0,0 +1,119 @@
+//! Tracks the operating system's light/dark appearance.
+//!
+//! On Windows the watcher waits on registry change notifications for the
+//! personalization key, so the theme flips as soon as the user changes it.
+//! Elsewhere (or if notifications fail) it falls back to a slow poll.
+//! Either way it wakes every second to notice a dropped subscription.
+
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, StreamExt};
+use iced::Subscription;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::theme::AppTheme;
+
+/// Poll interval used when the platform cannot notify us of changes.
+const FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest the watcher thread waits before checking the subscription is still there.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Reads the current system appearance. Unknown modes fall back to dark.
+pub fn detect() -> AppTheme {
+    match dark_light::detect() {
+        dark_light::Mode::Dark => AppTheme::Dark,
+        dark_light::Mode::Light => AppTheme::Light,
+        _ => AppTheme::Dark,
+    }
+}
+
+/// Emits the system theme every time it changes.
+pub fn subscribe() -> Subscription<AppTheme> {
+    struct SystemThemeWatcher;
+
+    iced::subscription::channel(
+        std::any::TypeId::of::<SystemThemeWatcher>(),
+        10,
+        |mut output| async move {
+            let (mut sender, mut receiver) = mpsc::channel(10);
+
+            thread::spawn(move || {
+                let mut last = detect();
+                // Closed once the subscription is dropped (e.g. user picked a fixed theme).
+                while !sender.is_closed() {
+                    if !platform::wait_for_change(STOP_CHECK_INTERVAL) {
+                        let polled = Instant::now();
+                        while polled.elapsed() < FALLBACK_POLL_INTERVAL && !sender.is_closed() {
+                            thread::sleep(STOP_CHECK_INTERVAL);
+                        }
+                    }
+
+                    let current = detect();
+                    if current != last {
+                        last = current;
+                        let _ = sender.try_send(current);
+                    }
+                }
+            });
+
+            while let Some(theme) = receiver.next().await {
+                let _ = output.send(theme).await;
+            }
+
+            loop {
+                std::future::pending::<()>().await;
+            }
+        },
+    )
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ptr;
+    use std::time::Duration;
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_SUCCESS, WAIT_OBJECT_0, WAIT_TIMEOUT};
+    use windows_sys::Win32::System::Registry::{
+        RegCloseKey, RegNotifyChangeKeyValue, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER,
+        KEY_NOTIFY, REG_NOTIFY_CHANGE_LAST_SET,
+    };
+    use windows_sys::Win32::System::Threading::{CreateEventW, WaitForSingleObject};
+
+    // `dark_light` reads `AppsUseLightTheme` from this key.
+    const PERSONALIZE_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize";
+
+    /// Blocks until a value under the personalization key changes or
+    /// `timeout` passes. Returns `false` if notifications are unavailable.
+    pub fn wait_for_change(timeout: Duration) -> bool {
+        let path: Vec<u16> = PERSONALIZE_KEY.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut key: HKEY = 0;
+
+        unsafe {
+            if RegOpenKeyExW(HKEY_CURRENT_USER, path.as_ptr(), 0, KEY_NOTIFY, &mut key) != ERROR_SUCCESS {
+                return false;
+            }
+            let event = CreateEventW(ptr::null(), 0, 0, ptr::null());
+            if event == 0 {
+                RegCloseKey(key);
+                return false;
+            }
+            // Asynchronous: the event is signaled once a value changes.
+            let status = RegNotifyChangeKeyValue(key, 0, REG_NOTIFY_CHANGE_LAST_SET, event, 1);
+            let waited = status == ERROR_SUCCESS
+                && matches!(WaitForSingleObject(event, timeout.as_millis() as u32), WAIT_OBJECT_0 | WAIT_TIMEOUT);
+            CloseHandle(event);
+            RegCloseKey(key);
+            waited
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use std::time::Duration;
+
+    /// No change notifications here; the caller falls back to polling.
+    pub fn wait_for_change(_timeout: Duration) -> bool {
+        false
+    }
+}
@@ -4,14 +4,26 @@ use iced::widget::{
     button, checkbox, container, pick_list, scrollable, text, text_input,
 };
 use iced::{application, overlay, Border, Color};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use super_clicker::settings::Settings;
 
 // Our custom theme enum.
 // We derive Default and set Light as the default theme.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+// iced hands the theme around by value, so it stays `Copy` despite the palette size.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum AppTheme {
     #[default]
     Light,
     Dark,
+    /// Maximum-contrast palette for low-vision users (WCAG AAA).
+    HighContrast,
+    /// A user palette loaded from `palette.json` in the config directory.
+    Custom(Palette),
 }
 
 #[derive(Clone, Copy, Default)]
@@ -22,71 +34,301 @@ pub enum TextStyle {
     Accent,
 }
 
+// --- Color Palette ---
+
+/// Every color the style sheets use. Built-in themes provide their own,
+/// `AppTheme::Custom` carries one loaded from disk.
+///
+/// Colors are stored as `#RRGGBB` strings in the palette file. Missing
+/// entries fall back to the light palette.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    #[serde(with = "hex_color")]
+    pub primary_background: Color,
+    #[serde(with = "hex_color")]
+    pub card_background: Color,
+    #[serde(with = "hex_color")]
+    pub text_primary: Color,
+    #[serde(with = "hex_color")]
+    pub text_disabled: Color,
+    /// Text drawn on top of `accent` (button labels).
+    #[serde(with = "hex_color")]
+    pub text_on_accent: Color,
+    #[serde(with = "hex_color")]
+    pub accent: Color,
+    #[serde(with = "hex_color")]
+    pub status_running: Color,
+    #[serde(with = "hex_color")]
+    pub status_running_text: Color,
+    #[serde(with = "hex_color")]
+    pub status_stopped: Color,
+    #[serde(with = "hex_color")]
+    pub status_stopped_text: Color,
+    #[serde(with = "hex_color")]
+    pub button_disabled_background: Color,
+    #[serde(with = "hex_color")]
+    pub input_background: Color,
+    #[serde(with = "hex_color")]
+    pub input_border: Color,
+}
+
+impl Palette {
+    pub fn light() -> Self {
+        Palette {
+            primary_background: Color::WHITE,
+            card_background: Color::from_rgb(0.95, 0.95, 0.95),
+            text_primary: Color::BLACK,
+            text_disabled: Color::from_rgb8(89, 89, 89),           // #595959
+            text_on_accent: Color::WHITE,
+            accent: Color::from_rgb8(0, 90, 158),                  // #005A9E - WCAG AA+ compliant with white text
+            status_running: Color::from_rgb8(46, 125, 50),         // #2E7D32
+            status_running_text: Color::WHITE,
+            status_stopped: Color::from_rgb8(224, 224, 224),
+            status_stopped_text: Color::from_rgb8(51, 51, 51),     // #333333
+            button_disabled_background: Color::from_rgb8(224, 224, 224), // #E0E0E0
+            input_background: Color::WHITE,
+            input_border: Color::from_rgb(0.7, 0.7, 0.7),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Palette {
+            primary_background: Color::from_rgb(0.1, 0.1, 0.1),
+            card_background: Color::from_rgb(0.15, 0.15, 0.15),
+            text_primary: Color::WHITE,
+            text_disabled: Color::from_rgb8(142, 142, 142),        // #8E8E8E
+            text_on_accent: Color::WHITE,
+            accent: Color::from_rgb8(0, 90, 158),                  // #005A9E
+            status_running: Color::from_rgb8(46, 125, 50),         // #2E7D32
+            status_running_text: Color::WHITE,
+            status_stopped: Color::from_rgb8(60, 60, 60),
+            status_stopped_text: Color::WHITE,
+            button_disabled_background: Color::from_rgb(0.2, 0.2, 0.2), // #333333
+            input_background: Color::from_rgb(0.1, 0.1, 0.1),
+            input_border: Color::from_rgb(0.3, 0.3, 0.3),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Palette {
+            primary_background: Color::BLACK,
+            card_background: Color::BLACK,
+            text_primary: Color::WHITE,
+            text_disabled: Color::from_rgb8(191, 191, 191),        // #BFBFBF
+            text_on_accent: Color::BLACK,
+            accent: Color::from_rgb8(255, 255, 0),                 // #FFFF00
+            status_running: Color::from_rgb8(63, 242, 63),         // #3FF23F
+            status_running_text: Color::BLACK,
+            status_stopped: Color::from_rgb8(51, 51, 51),          // #333333
+            status_stopped_text: Color::WHITE,
+            button_disabled_background: Color::from_rgb8(38, 38, 38), // #262626
+            input_background: Color::BLACK,
+            input_border: Color::WHITE,
+        }
+    }
+
+    /// Every foreground/background combination the style sheets draw.
+    pub fn contrast_pairs(&self) -> [ContrastPair; 9] {
+        let pair = |name, foreground, background, disabled| ContrastPair {
+            name,
+            foreground,
+            background,
+            disabled,
+        };
+
+        [
+            pair("text_primary on primary_background", self.text_primary, self.primary_background, false),
+            pair("text_primary on card_background", self.text_primary, self.card_background, false),
+            pair("text_primary on input_background", self.text_primary, self.input_background, false),
+            pair("text_on_accent on accent", self.text_on_accent, self.accent, false),
+            pair("status_running_text on status_running", self.status_running_text, self.status_running, false),
+            pair("status_stopped_text on status_stopped", self.status_stopped_text, self.status_stopped, false),
+            pair("text_disabled on primary_background", self.text_disabled, self.primary_background, true),
+            pair("text_disabled on card_background", self.text_disabled, self.card_background, true),
+            pair("text_disabled on button_disabled_background", self.text_disabled, self.button_disabled_background, true),
+        ]
+    }
+
+    /// Location of the user palette file.
+    pub fn path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Settings::config_dir()?.join("palette.json"))
+    }
+
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(Self::path()?)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Last modification time of the palette file, used for hot reload.
+    pub fn modified() -> Option<SystemTime> {
+        fs::metadata(Self::path().ok()?).and_then(|meta| meta.modified()).ok()
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::light()
+    }
+}
+
+// --- Contrast Checking ---
+
+/// WCAG AA minimum for normal text.
+pub const WCAG_AA_TEXT: f32 = 4.5;
+/// WCAG AA minimum for large text and UI components; we also hold disabled
+/// labels to it even though WCAG exempts them.
+pub const WCAG_AA_NON_TEXT: f32 = 3.0;
+/// WCAG AAA minimum for normal text, required by the high-contrast theme.
+pub const WCAG_AAA_TEXT: f32 = 7.0;
+
+/// A foreground color drawn on a background color somewhere in the UI.
+#[derive(Debug, Clone, Copy)]
+pub struct ContrastPair {
+    pub name: &'static str,
+    pub foreground: Color,
+    pub background: Color,
+    /// Whether this pair is only used for disabled controls.
+    pub disabled: bool,
+}
+
+/// WCAG 2.x relative luminance of an sRGB color.
+pub fn relative_luminance(color: Color) -> f32 {
+    let channel = |c: f32| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+}
+
+/// WCAG 2.x contrast ratio between two colors, from 1.0 (identical) to 21.0.
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// Serde adapter storing a `Color` as `#RRGGBB`.
+mod hex_color {
+    use iced::Color;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        let [r, g, b, _] = color.into_rgba8();
+        serializer.serialize_str(&format!("#{:02X}{:02X}{:02X}", r, g, b))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        parse(&value).ok_or_else(|| D::Error::custom(format!("invalid color `{}`, expected #RRGGBB", value)))
+    }
+
+    fn parse(value: &str) -> Option<Color> {
+        let hex = value.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let rgb = u32::from_str_radix(hex, 16).ok()?;
+        Some(Color::from_rgb8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+    }
+}
 
 // --- Color & Spacing Design Tokens ---
 impl AppTheme {
-    pub fn primary_background(self) -> Color {
+    pub fn palette(self) -> Palette {
         match self {
-            AppTheme::Light => Color::WHITE,
-            AppTheme::Dark => Color::from_rgb(0.1, 0.1, 0.1),
+            AppTheme::Light => Palette::light(),
+            AppTheme::Dark => Palette::dark(),
+            AppTheme::HighContrast => Palette::high_contrast(),
+            AppTheme::Custom(palette) => palette,
         }
     }
 
-    pub fn card_background(self) -> Color {
+    /// Minimum contrast ratio this theme must reach for `pair`.
+    pub fn required_contrast(self, pair: &ContrastPair) -> f32 {
         match self {
-            AppTheme::Light => Color::from_rgb(0.95, 0.95, 0.95),
-            AppTheme::Dark => Color::from_rgb(0.15, 0.15, 0.15),
+            AppTheme::HighContrast => WCAG_AAA_TEXT,
+            _ if pair.disabled => WCAG_AA_NON_TEXT,
+            _ => WCAG_AA_TEXT,
         }
     }
 
+    /// Describes every token pair that falls short of `required_contrast`.
+    pub fn contrast_failures(self) -> Vec<String> {
+        self.palette()
+            .contrast_pairs()
+            .iter()
+            .filter_map(|pair| {
+                let ratio = contrast_ratio(pair.foreground, pair.background);
+                let required = self.required_contrast(pair);
+                (ratio < required).then(|| format!("{}: {:.2}:1 (needs {:.1}:1)", pair.name, ratio, required))
+            })
+            .collect()
+    }
+
+    pub fn primary_background(self) -> Color {
+        self.palette().primary_background
+    }
+
+    pub fn card_background(self) -> Color {
+        self.palette().card_background
+    }
+
     pub fn text_primary(self) -> Color {
-        match self {
-            AppTheme::Light => Color::BLACK,
-            AppTheme::Dark => Color::WHITE,
-        }
+        self.palette().text_primary
     }
 
     pub fn text_disabled(self) -> Color {
-        match self {
-            AppTheme::Light => Color::from_rgb8(89, 89, 89),      // #595959
-            AppTheme::Dark => Color::from_rgb8(142, 142, 142),   // #8E8E8E
-        }
+        self.palette().text_disabled
     }
 
-    pub fn accent_blue(self) -> Color {
-        Color::from_rgb8(0, 90, 158) // #005A9E - WCAG AA+ compliant with white text
+    pub fn text_on_accent(self) -> Color {
+        self.palette().text_on_accent
     }
 
-    pub fn status_running_green(self) -> Color {
-        Color::from_rgb8(46, 125, 50) // #2E7D32
+    pub fn accent(self) -> Color {
+        self.palette().accent
     }
 
-    pub fn status_stopped_grey(self) -> Color {
-        match self {
-            AppTheme::Light => Color::from_rgb8(224, 224, 224),
-            AppTheme::Dark => Color::from_rgb8(60, 60, 60),
-        }
+    pub fn status_running(self) -> Color {
+        self.palette().status_running
+    }
+
+    pub fn status_running_text(self) -> Color {
+        self.palette().status_running_text
+    }
+
+    pub fn status_stopped(self) -> Color {
+        self.palette().status_stopped
+    }
+
+    pub fn status_stopped_text(self) -> Color {
+        self.palette().status_stopped_text
     }
 
     pub fn button_disabled_background(self) -> Color {
-        match self {
-            AppTheme::Light => Color::from_rgb8(224, 224, 224), // #E0E0E0
-            AppTheme::Dark => Color::from_rgb(0.2, 0.2, 0.2),    // #333333
-        }
+        self.palette().button_disabled_background
     }
 
     pub fn input_background(self) -> Color {
-        match self {
-            AppTheme::Light => Color::WHITE,
-            AppTheme::Dark => Color::from_rgb(0.1, 0.1, 0.1),
-        }
+        self.palette().input_background
     }
 
     pub fn input_border(self) -> Color {
-        match self {
-            AppTheme::Light => Color::from_rgb(0.7, 0.7, 0.7),
-            AppTheme::Dark => Color::from_rgb(0.3, 0.3, 0.3),
-        }
+        self.palette().input_border
     }
 }
 
@@ -131,11 +373,8 @@ impl container::StyleSheet for AppTheme {
                 ..Default::default()
             },
             ContainerStyle::StatusDisplay => container::Appearance {
-                background: Some(self.status_stopped_grey().into()),
-                text_color: Some(match self {
-                    AppTheme::Light => Color::from_rgb8(51, 51, 51), // #333333
-                    AppTheme::Dark => Color::WHITE,
-                }),
+                background: Some(self.status_stopped().into()),
+                text_color: Some(self.status_stopped_text()),
                 border: Border {
                     radius: 8.0.into(),
                     ..Default::default()
@@ -143,8 +382,8 @@ impl container::StyleSheet for AppTheme {
                 ..Default::default()
             },
             ContainerStyle::StatusDisplayRunning => container::Appearance {
-                background: Some(self.status_running_green().into()),
-                text_color: Some(Color::WHITE),
+                background: Some(self.status_running().into()),
+                text_color: Some(self.status_running_text()),
                 border: Border {
                     radius: 8.0.into(),
                     ..Default::default()
@@ -177,12 +416,12 @@ impl button::StyleSheet for AppTheme {
 
     fn active(&self, _style: &Self::Style) -> button::Appearance {
         button::Appearance {
-            background: Some(self.accent_blue().into()),
+            background: Some(self.accent().into()),
             border: Border {
                 radius: 8.0.into(),
                 ..Default::default()
             },
-            text_color: Color::WHITE, // This is a suggestion, the Text widget style will override
+            text_color: self.text_on_accent(), // This is a suggestion, the Text widget style will override
             ..Default::default()
         }
     }
@@ -217,7 +456,7 @@ impl text_input::StyleSheet for AppTheme {
     fn focused(&self, style: &Self::Style) -> text_input::Appearance {
         text_input::Appearance {
             border: Border {
-                color: self.accent_blue(),
+                color: self.accent(),
                 ..self.active(style).border
             },
             ..self.active(style)
@@ -248,7 +487,7 @@ impl text_input::StyleSheet for AppTheme {
     }
 
     fn selection_color(&self, _style: &Self::Style) -> Color {
-        self.accent_blue()
+        self.accent()
     }
 }
 
@@ -273,7 +512,7 @@ impl pick_list::StyleSheet for AppTheme {
     fn hovered(&self, style: &Self::Style) -> pick_list::Appearance {
         pick_list::Appearance {
             border: Border {
-                color: self.accent_blue(),
+                color: self.accent(),
                 ..self.active(style).border
             },
             ..self.active(style)
@@ -293,7 +532,7 @@ impl checkbox::StyleSheet for AppTheme {
                 width: 1.0,
                 color: self.input_border(),
             },
-            icon_color: self.accent_blue(),
+            icon_color: self.accent(),
             text_color: Some(self.text_primary()),
         }
     }
@@ -301,7 +540,7 @@ impl checkbox::StyleSheet for AppTheme {
     fn hovered(&self, style: &Self::Style, is_checked: bool) -> checkbox::Appearance {
         checkbox::Appearance {
             border: Border {
-                color: self.accent_blue(),
+                color: self.accent(),
                 ..self.active(style, is_checked).border
             },
             ..self.active(style, is_checked)
@@ -331,7 +570,7 @@ impl text::StyleSheet for AppTheme {
         let color = match style {
             TextStyle::Default => self.text_primary(),
             TextStyle::Disabled => self.text_disabled(),
-            TextStyle::Accent => Color::WHITE,
+            TextStyle::Accent => self.text_on_accent(),
         };
         text::Appearance { color: Some(color) }
     }
@@ -369,6 +608,59 @@ impl From<AppTheme> for iced::Theme {
         match theme {
             AppTheme::Light => iced::Theme::Light,
             AppTheme::Dark => iced::Theme::Dark,
+            AppTheme::HighContrast | AppTheme::Custom(_) => {
+                let palette = theme.palette();
+                iced::Theme::custom(
+                    String::from("Custom"),
+                    iced::theme::Palette {
+                        background: palette.primary_background,
+                        text: palette.text_primary,
+                        primary: palette.accent,
+                        success: palette.status_running,
+                        danger: iced::theme::Palette::DARK.danger,
+                    },
+                )
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILT_IN: [AppTheme; 3] = [AppTheme::Light, AppTheme::Dark, AppTheme::HighContrast];
+
+    #[test]
+    fn contrast_ratio_spans_one_to_twenty_one() {
+        assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(Color::WHITE, Color::BLACK) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(Color::WHITE, Color::WHITE) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn built_in_themes_meet_contrast_requirements() {
+        for theme in BUILT_IN {
+            let failures = theme.contrast_failures();
+            assert!(failures.is_empty(), "{:?} fails contrast checks: {:#?}", theme, failures);
+        }
+    }
+
+    #[test]
+    fn high_contrast_theme_meets_aaa_everywhere() {
+        let theme = AppTheme::HighContrast;
+        for pair in theme.palette().contrast_pairs() {
+            assert!(contrast_ratio(pair.foreground, pair.background) >= WCAG_AAA_TEXT, "{}", pair.name);
+        }
+    }
+
+    #[test]
+    fn low_contrast_disabled_text_is_reported() {
+        let palette = Palette {
+            text_disabled: Color::from_rgb8(200, 200, 200),
+            ..Palette::light()
+        };
+        let failures = AppTheme::Custom(palette).contrast_failures();
+        assert!(failures.iter().any(|failure| failure.starts_with("text_disabled on button_disabled_background")));
+    }
+}
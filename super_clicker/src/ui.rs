@@ -1,16 +1,41 @@
-use iced::widget::{button, checkbox, column, container, pick_list, text, text_input, Row, Space};
-use iced::{Alignment, Element, Length};
+use iced::widget::{button, checkbox, column, container, mouse_area, pick_list, scrollable, text, text_input, Column, Row, Space};
+use iced::{Alignment, Element, Length, Size};
 
-use super::app::Message;
+use super::app::{BenchmarkPanel, BundlePanel, Clicker, ClickerChoice, HotkeyField, Message, ScheduleForm, ScriptPanel};
+use super_clicker::history::ExportFormat;
+use super_clicker::settings::{
+    Action, ActionKind, AreaShape, IntervalStepMode, LogLevel, Motion, MotionKind, PatternStep, RateLimitSettings,
+    Schedule, ScheduleRepeat, Settings, Spread, StepKind, ThemePreference,
+};
 use super::theme::{self, AppTheme, TextStyle};
 
+/// Size of the full settings window.
+pub const WINDOW_SIZE: Size = Size::new(420.0, 680.0);
+/// Size of the borderless always-on-top strip shown in mini mode.
+pub const MINI_WINDOW_SIZE: Size = Size::new(320.0, 44.0);
+
+#[allow(clippy::too_many_arguments)]
 pub fn view(
     status: &str,
-    interval_input: &str,
-    mouse_button_selected: String,
-    enable_dynamic_adjustment: bool,
-    is_running: bool,
+    clickers: &[Clicker],
+    settings: &Settings,
+    start_delay_input: &str,
+    schedule_form: &ScheduleForm,
+    next_fires: &[Option<String>],
+    scripts: &ScriptPanel,
+    log_lines: Option<&[String]>,
+    recent_sessions: &[String],
+    benchmark: &BenchmarkPanel,
+    bundle: &BundlePanel,
+    settings_error: Option<&str>,
 ) -> Element<'static, Message, AppTheme> {
+    let is_running = scripts.run.is_some()
+        || clickers.iter().any(|clicker| clicker.is_running || clicker.countdown.is_some());
+    let interval_step_mode = settings.interval_step_mode;
+    let theme_preference = settings.theme_preference;
+    let start_delay_tick = settings.start_delay_tick;
+    let enable_dynamic_adjustment = settings.enable_dynamic_adjustment;
+
     // --- Status ---
     let status_display = container(text(format!("Status: {}", status)).size(20))
         .padding(10)
@@ -22,62 +47,118 @@ pub fn view(
             theme::ContainerStyle::StatusDisplay
         });
 
-    // --- Primary Controls ---
-    let primary_controls_title = text("Primary Controls").size(18);
+    // Stays up until settings.json is fixed, unlike the status.
+    let settings_notice = settings_error.map(|error| {
+        container(text(format!(
+            "settings.json could not be used, so changes are not saved until it is fixed: {}",
+            error
+        )))
+        .padding(10)
+        .width(Length::Fill)
+        .style(theme::ContainerStyle::Card)
+    });
+
+    // --- Clickers ---
+    let can_remove = clickers.len() > 1;
+    let clicker_cards: Column<'static, Message, AppTheme> = clickers
+        .iter()
+        .enumerate()
+        .fold(Column::new().spacing(10), |cards, (index, clicker)| {
+            cards.push(clicker_card(index, clicker, can_remove))
+        })
+        .push(schedules_card(&settings.schedules, clickers, schedule_form, next_fires))
+        .push(scripts_card(scripts, &settings.scripts.stop_hotkey.to_string()))
+        .push(history_card(recent_sessions))
+        .push(bundle_card(bundle, clickers))
+        .push(timing_card(benchmark, settings.timing_offset_us))
+        .push(log_card(settings.log_level, log_lines));
+
+    // --- General Settings ---
+    let general_title = text("General").size(18);
 
-    let interval_control: Element<_, _, _> = if is_running {
+    let step_mode_control: Element<_, _, _> = if is_running {
         Row::new()
             .spacing(10)
             .align_items(Alignment::Center)
-            .push(text("Click Interval (ms):").style(TextStyle::Disabled))
+            .push(text("Interval Steps:").style(TextStyle::Disabled))
             .push(Space::with_width(Length::Fill))
             .push(
-                container(text(interval_input))
+                container(text(interval_step_mode.to_string()))
                     .style(theme::ContainerStyle::ReadOnly)
                     .padding(10)
-                    .width(Length::Fixed(80.0))
+                    .width(Length::Fixed(160.0))
             )
             .into()
     } else {
         Row::new()
             .spacing(10)
             .align_items(Alignment::Center)
-            .push(text("Click Interval (ms):"))
+            .push(text("Interval Steps:"))
             .push(Space::with_width(Length::Fill))
-            .push(text_input("ms", interval_input)
-                .on_input(Message::IntervalInputChanged)
-                .padding(10)
-                .width(Length::Fixed(80.0)))
+            .push(pick_list(
+                IntervalStepMode::ALL,
+                Some(interval_step_mode),
+                Message::IntervalStepModeSelected,
+            ).padding(10).width(Length::Fixed(160.0)))
             .into()
     };
 
-    let mouse_button_control: Element<_, _, _> = if is_running {
+    let start_delay_control: Element<_, _, _> = if is_running {
         Row::new()
             .spacing(10)
             .align_items(Alignment::Center)
-            .push(text("Mouse Button:").style(TextStyle::Disabled))
+            .push(text("Start Delay (s):").style(TextStyle::Disabled))
             .push(Space::with_width(Length::Fill))
+            .push(checkbox("Tick", start_delay_tick))
             .push(
-                container(text(mouse_button_selected))
+                container(text(start_delay_input))
                     .style(theme::ContainerStyle::ReadOnly)
                     .padding(10)
-                    .width(Length::Fixed(120.0))
+                    .width(Length::Fixed(80.0))
             )
             .into()
     } else {
         Row::new()
             .spacing(10)
             .align_items(Alignment::Center)
-            .push(text("Mouse Button:"))
+            .push(text("Start Delay (s):"))
             .push(Space::with_width(Length::Fill))
-            .push(pick_list(
-                vec!["Left".to_string(), "Middle".to_string(), "Right".to_string()],
-                Some(mouse_button_selected),
-                Message::MouseButtonSelected,
-            ).padding(10).width(Length::Fixed(120.0)))
+            .push(checkbox("Tick", start_delay_tick).on_toggle(Message::StartDelayTickToggled))
+            .push(text_input("s", start_delay_input)
+                .on_input(Message::StartDelayInputChanged)
+                .padding(10)
+                .width(Length::Fixed(80.0)))
             .into()
     };
-    
+
+    // Theme choice is purely cosmetic, so it stays editable while running.
+    let theme_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Theme:"))
+        .push(Space::with_width(Length::Fill))
+        .push(pick_list(
+            ThemePreference::ALL,
+            Some(theme_preference),
+            Message::ThemePreferenceSelected,
+        ).padding(10).width(Length::Fixed(160.0)));
+
+    // Safety settings, so they stay editable while running.
+    let idle_minutes = match settings.auto_stop.idle_secs {
+        0 => String::new(),
+        secs => (secs / 60).to_string(),
+    };
+    let auto_stop_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Stop When Idle (min):"))
+        .push(Space::with_width(Length::Fill))
+        .push(checkbox("On lock", settings.auto_stop.stop_on_lock).on_toggle(Message::AutoStopOnLockToggled))
+        .push(text_input("off", &idle_minutes)
+            .on_input(Message::AutoStopIdleChanged)
+            .padding(10)
+            .width(Length::Fixed(80.0)));
+
     let dynamic_adj_checkbox = checkbox(
         "Enable Dynamic Interval Adjustment",
         enable_dynamic_adjustment,
@@ -89,43 +170,45 @@ pub fn view(
         dynamic_adj_checkbox.on_toggle(Message::DynamicAdjustmentToggled)
     };
 
-    let (start_btn_content, stop_btn_content) = if is_running {
-        (
-            text("Start").style(TextStyle::Disabled),
-            text("Stop").style(TextStyle::Accent),
-        )
+    let add_btn = button(text("Add Clicker").style(TextStyle::Accent))
+        .style(theme::ButtonStyle::Primary)
+        .padding([10, 20])
+        .on_press(Message::AddClicker);
+
+    let stop_all_content = if is_running {
+        text("Stop All").style(TextStyle::Accent)
     } else {
-        (
-            text("Start").style(TextStyle::Accent),
-            text("Stop").style(TextStyle::Disabled),
-        )
+        text("Stop All").style(TextStyle::Disabled)
     };
-
-    let start_btn = button(start_btn_content.width(Length::Fill))
+    let stop_all_btn = button(stop_all_content)
         .style(theme::ButtonStyle::Primary)
         .padding([10, 20]);
-    let stop_btn = button(stop_btn_content.width(Length::Fill))
-        .style(theme::ButtonStyle::Primary)
-        .padding([10, 20]);
-    
-    let (start_btn, stop_btn) = if is_running {
-        (start_btn, stop_btn.on_press(Message::Stop))
+    let stop_all_btn = if is_running {
+        stop_all_btn.on_press(Message::StopAll)
     } else {
-        (start_btn.on_press(Message::Start), stop_btn)
+        stop_all_btn
     };
 
+    let mini_mode_btn = button(text("Mini").style(TextStyle::Accent))
+        .style(theme::ButtonStyle::Primary)
+        .padding([10, 20])
+        .on_press(Message::ToggleMiniMode);
+
     let action_buttons = Row::new()
         .spacing(10)
-        .push(start_btn)
-        .push(stop_btn);
+        .push(add_btn)
+        .push(stop_all_btn)
+        .push(mini_mode_btn);
 
     // --- Assembly ---
-    let main_controls_card = container(
+    let general_card = container(
         column![
-            primary_controls_title,
-            interval_control,
-            mouse_button_control,
+            general_title,
             dynamic_adj_checkbox_styled,
+            step_mode_control,
+            start_delay_control,
+            theme_control,
+            auto_stop_control,
         ]
         .spacing(15),
     )
@@ -133,13 +216,742 @@ pub fn view(
     .padding(15);
     
     // Final Column
+    let mut layout = Column::new().spacing(20).padding(10).push(status_display);
+    if let Some(notice) = settings_notice {
+        layout = layout.push(notice);
+    }
     container(
-        column![status_display, main_controls_card, action_buttons]
-            .spacing(20)
-            .padding(10)
+        layout
+            .push(scrollable(clicker_cards).height(Length::Fill))
+            .push(general_card)
+            .push(action_buttons)
     )
     .width(Length::Fill)
     .height(Length::Fill)
     .center_x()
     .into()
 }
+
+/// One clicker: action, interval, target and toggle hotkey, plus its own Start/Stop.
+/// Fields are read-only while the clicker is running or counting down.
+fn clicker_card(index: usize, clicker: &Clicker, can_remove: bool) -> Element<'static, Message, AppTheme> {
+    let config = &clicker.config;
+    let is_active = clicker.is_running || clicker.countdown.is_some();
+
+    let state = match clicker.countdown {
+        Some(seconds) => format!("Starting in {}...", seconds),
+        None if clicker.is_running => format!("Running - {} clicks", clicker.click_count),
+        None => String::from("Stopped"),
+    };
+
+    let (start_stop_label, start_stop_message) = if is_active {
+        ("Stop", Message::StopClicker(index))
+    } else {
+        ("Start", Message::StartClicker(index))
+    };
+    let start_stop_btn = button(text(start_stop_label).style(TextStyle::Accent))
+        .style(theme::ButtonStyle::Primary)
+        .padding([6, 14])
+        .on_press(start_stop_message);
+
+    if is_active {
+        let target = config
+            .target
+            .map_or(String::from("cursor"), |(x, y)| format!("{}, {}", x, y));
+        let timing = if config.pattern.is_empty() {
+            format!("{} every {} ms", action_label(&config.action), clicker.interval_input)
+        } else {
+            format!("Pattern of {} steps", config.pattern.len())
+        };
+        let summary = format!("{} at {} ({})", timing, target, config.hotkey);
+
+        return container(
+            column![
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(config.name.clone()).size(18))
+                    .push(Space::with_width(Length::Fill))
+                    .push(text(state).style(TextStyle::Disabled))
+                    .push(start_stop_btn),
+                text(summary).style(TextStyle::Disabled),
+            ]
+            .spacing(10),
+        )
+        .style(theme::ContainerStyle::Card)
+        .padding(15)
+        .into();
+    }
+
+    let remove_btn = button(text("Remove").style(if can_remove {
+        TextStyle::Accent
+    } else {
+        TextStyle::Disabled
+    }))
+    .style(theme::ButtonStyle::Primary)
+    .padding([6, 14]);
+    let remove_btn = if can_remove {
+        remove_btn.on_press(Message::RemoveClicker(index))
+    } else {
+        remove_btn
+    };
+
+    let header = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text_input("Name", &config.name)
+            .on_input(move |value| Message::ClickerNameChanged(index, value))
+            .padding(6)
+            .width(Length::Fill))
+        .push(remove_btn)
+        .push(start_stop_btn);
+
+    let mut action_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Action:"))
+        .push(Space::with_width(Length::Fill))
+        .push(pick_list(
+            ActionKind::ALL,
+            Some(config.action.kind()),
+            move |kind| Message::ClickerActionSelected(index, kind),
+        ).padding(6).width(Length::Fixed(140.0)));
+    match &config.action {
+        Action::Press(key) => {
+            action_control = action_control.push(text_input("Key", key)
+                .on_input(move |value| Message::ClickerKeyChanged(index, value))
+                .padding(6)
+                .width(Length::Fixed(80.0)));
+        }
+        Action::Scroll(..) => {
+            action_control = action_control.push(text_input("Notches", &clicker.scroll_input)
+                .on_input(move |value| Message::ClickerScrollChanged(index, value))
+                .padding(6)
+                .width(Length::Fixed(80.0)));
+        }
+        Action::Click(_) => {}
+    }
+
+    let interval_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Interval (ms):"))
+        .push(text_input("ms", &clicker.interval_input)
+            .on_input(move |value| Message::ClickerIntervalChanged(index, value))
+            .on_submit(Message::ClickerIntervalSubmitted(index))
+            .padding(6)
+            .width(Length::Fixed(70.0)))
+        .push(Space::with_width(Length::Fill))
+        .push(text("Target:"))
+        .push(text_input("cursor", &clicker.target_input)
+            .on_input(move |value| Message::ClickerTargetChanged(index, value))
+            .padding(6)
+            .width(Length::Fixed(90.0)));
+
+    // A captured area takes the place of the fixed target.
+    let mut area_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Area:"));
+    if let Some(area) = config.area {
+        area_control = area_control
+            .push(pick_list(
+                AreaShape::ALL,
+                Some(area.shape),
+                move |shape| Message::AreaShapeSelected(index, shape),
+            ).padding(6).width(Length::Fixed(100.0)))
+            .push(pick_list(
+                Spread::ALL,
+                Some(area.spread),
+                move |spread| Message::AreaSpreadSelected(index, spread),
+            ).padding(6).width(Length::Fixed(95.0)));
+    } else {
+        area_control = area_control.push(text("none, clicks hit the target").style(TextStyle::Disabled));
+    }
+    area_control = area_control
+        .push(Space::with_width(Length::Fill))
+        .push(button(text(if clicker.capture.is_some() { "Cancel" } else { "Capture" }).style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 10])
+            .on_press(Message::CaptureArea(index)));
+    if config.area.is_some() {
+        area_control = area_control.push(button(text("X").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 10])
+            .on_press(Message::ClearArea(index)));
+    }
+
+    let mut motion_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Movement:"))
+        .push(pick_list(
+            MotionKind::ALL,
+            Some(config.motion.kind()),
+            move |kind| Message::ClickerMotionSelected(index, kind),
+        ).padding(6).width(Length::Fixed(90.0)));
+    let motion_placeholder = match config.motion {
+        Motion::Still => None,
+        Motion::Jiggle { .. } => Some("px"),
+        Motion::Path { .. } => Some("x, y; x, y; ..."),
+        Motion::Circle { .. } => Some("x, y, radius"),
+    };
+    if let Some(placeholder) = motion_placeholder {
+        motion_control = motion_control.push(text_input(placeholder, &clicker.motion_input)
+            .on_input(move |value| Message::ClickerMotionChanged(index, value))
+            .padding(6)
+            .width(Length::Fill));
+    }
+    if matches!(config.motion, Motion::Path { .. } | Motion::Circle { .. }) {
+        motion_control = motion_control.push(text_input("px/s", &clicker.speed_input)
+            .on_input(move |value| Message::ClickerMotionSpeedChanged(index, value))
+            .padding(6)
+            .width(Length::Fixed(55.0)));
+    }
+
+    let glide_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Glide (ms):"))
+        .push(text_input("0", &clicker.glide_input)
+            .on_input(move |value| Message::ClickerGlideChanged(index, value))
+            .padding(6)
+            .width(Length::Fixed(70.0)))
+        .push(text("Curve to targets; 0 jumps").style(TextStyle::Disabled));
+
+    let pattern_control = pattern_editor(index, clicker);
+
+    let hotkey = &config.hotkey;
+    let hotkey_control = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Hotkey:"))
+        .push(Space::with_width(Length::Fill))
+        .push(checkbox("Ctrl", hotkey.ctrl)
+            .on_toggle(move |value| Message::ClickerHotkeyChanged(index, HotkeyField::Ctrl(value))))
+        .push(checkbox("Alt", hotkey.alt)
+            .on_toggle(move |value| Message::ClickerHotkeyChanged(index, HotkeyField::Alt(value))))
+        .push(checkbox("Shift", hotkey.shift)
+            .on_toggle(move |value| Message::ClickerHotkeyChanged(index, HotkeyField::Shift(value))))
+        .push(text_input("Key", hotkey.key.as_deref().unwrap_or(""))
+            .on_input(move |value| Message::ClickerHotkeyChanged(index, HotkeyField::Key(value)))
+            .padding(6)
+            .width(Length::Fixed(60.0)));
+
+    container(
+        column![
+            header,
+            action_control,
+            interval_control,
+            area_control,
+            motion_control,
+            glide_control,
+            pattern_control,
+            hotkey_control,
+        ]
+        .spacing(10),
+    )
+    .style(theme::ContainerStyle::Card)
+    .padding(15)
+    .into()
+}
+
+/// Timetable: one row per schedule with its next fire time and an enable
+/// toggle, followed by a row to add a new one.
+fn schedules_card(
+    schedules: &[Schedule],
+    clickers: &[Clicker],
+    form: &ScheduleForm,
+    next_fires: &[Option<String>],
+) -> Element<'static, Message, AppTheme> {
+    let mut rows = Column::new().spacing(10).push(text("Schedules").size(18));
+
+    if schedules.is_empty() {
+        rows = rows.push(text("No scheduled runs.").style(TextStyle::Disabled));
+    }
+
+    for (index, schedule) in schedules.iter().enumerate() {
+        let duration = match schedule.run_secs {
+            0 => String::from("until stopped"),
+            secs => format!("for {} s", secs),
+        };
+        let clicker_name = clickers
+            .iter()
+            .find(|clicker| clicker.config.id == schedule.clicker_id)
+            .map_or("(removed clicker)", |clicker| clicker.config.name.as_str());
+        let next = match next_fires.get(index).cloned().flatten() {
+            Some(next) => format!("Next: {}", next),
+            None => String::from("Next: -"),
+        };
+
+        rows = rows.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(checkbox("", schedule.enabled)
+                    .on_toggle(move |enabled| Message::ScheduleToggled(index, enabled)))
+                .push(
+                    column![
+                        text(format!("{}: {} {}", clicker_name, schedule.kind, duration)),
+                        text(next).size(14).style(TextStyle::Disabled),
+                    ]
+                    .spacing(2)
+                    .width(Length::Fill),
+                )
+                .push(
+                    button(text("Remove").style(TextStyle::Accent))
+                        .style(theme::ButtonStyle::Primary)
+                        .padding([6, 14])
+                        .on_press(Message::RemoveSchedule(index)),
+                ),
+        );
+    }
+
+    let choices: Vec<ClickerChoice> = clickers
+        .iter()
+        .map(|clicker| ClickerChoice { id: clicker.config.id, name: clicker.config.name.clone() })
+        .collect();
+    let selected = choices.iter().find(|choice| Some(choice.id) == form.clicker).cloned();
+    let when_placeholder = match form.repeat {
+        ScheduleRepeat::Every => "min",
+        _ => "HH:MM",
+    };
+    let add_row = Row::new()
+        .spacing(6)
+        .align_items(Alignment::Center)
+        .push(pick_list(choices, selected, Message::ScheduleClickerSelected)
+            .placeholder("Clicker")
+            .padding(6)
+            .width(Length::Fill))
+        .push(pick_list(ScheduleRepeat::ALL, Some(form.repeat), Message::ScheduleRepeatSelected)
+            .padding(6)
+            .width(Length::Fixed(110.0)))
+        .push(text_input(when_placeholder, &form.when_input)
+            .on_input(Message::ScheduleWhenChanged)
+            .padding(6)
+            .width(Length::Fixed(60.0)))
+        .push(text_input("run s", &form.run_secs_input)
+            .on_input(Message::ScheduleRunSecsChanged)
+            .padding(6)
+            .width(Length::Fixed(50.0)))
+        .push(button(text("Add").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 10])
+            .on_press(Message::AddSchedule));
+
+    container(rows.push(add_row).push(
+        text("Run time in seconds; 0 keeps clicking until stopped.").size(14).style(TextStyle::Disabled),
+    ))
+    .style(theme::ContainerStyle::Card)
+    .padding(15)
+    .into()
+}
+
+/// The clicker's repeating step list. With no steps the clicker just repeats
+/// its action every interval.
+fn pattern_editor(index: usize, clicker: &Clicker) -> Element<'static, Message, AppTheme> {
+    let pattern = &clicker.config.pattern;
+
+    let hint = if pattern.is_empty() {
+        "Pattern: none, repeats the action above."
+    } else {
+        "Pattern (replaces action and interval):"
+    };
+    // Import/export as text: `macros/<clicker name>.txt` in the config directory.
+    let file_buttons = Row::new()
+        .spacing(10)
+        .push(Space::with_width(Length::Fill))
+        .push(button(text("Import").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 10])
+            .on_press(Message::PatternImported(index)))
+        .push(button(text("Export").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 10])
+            .on_press(Message::PatternExported(index)))
+        .push(button(text("Add Step").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 10])
+            .on_press(Message::PatternStepAdded(index)));
+
+    pattern.iter().enumerate().fold(
+        Column::new().spacing(6).push(text(hint).style(TextStyle::Disabled)).push(file_buttons),
+        |steps, (step_index, step)| {
+            let mut row = Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(text(format!("{}.", step_index + 1)).width(Length::Fixed(24.0)))
+                .push(pick_list(
+                    StepKind::ALL,
+                    Some(step.kind()),
+                    move |kind| Message::PatternStepKindSelected(index, step_index, kind),
+                ).padding(6).width(Length::Fixed(140.0)));
+
+            // Clicks have no value to edit.
+            let placeholder = match step {
+                PatternStep::Wait(_) => Some("ms"),
+                PatternStep::MoveTo(..) => Some("x, y"),
+                PatternStep::Act(Action::Press(_)) => Some("Key"),
+                PatternStep::Act(Action::Scroll(..)) => Some("Notches"),
+                PatternStep::Act(Action::Click(_)) => None,
+            };
+            if let Some(placeholder) = placeholder {
+                let value = clicker.step_inputs.get(step_index).map_or("", String::as_str);
+                row = row.push(text_input(placeholder, value)
+                    .on_input(move |value| Message::PatternStepValueChanged(index, step_index, value))
+                    .padding(6)
+                    .width(Length::Fixed(70.0)));
+            }
+
+            steps.push(row
+                .push(Space::with_width(Length::Fill))
+                .push(button(text("X").style(TextStyle::Accent))
+                    .style(theme::ButtonStyle::Primary)
+                    .padding([6, 10])
+                    .on_press(Message::PatternStepRemoved(index, step_index))))
+        },
+    )
+    .into()
+}
+
+/// Picks a script from the scripts folder and runs or stops it.
+fn scripts_card(scripts: &ScriptPanel, stop_hotkey: &str) -> Element<'static, Message, AppTheme> {
+    let state = match &scripts.run {
+        Some(run) => format!("Running {} - {} actions", run.name, run.actions()),
+        None if scripts.names.is_empty() => String::from("No scripts in the scripts folder."),
+        None => format!("Stop with {} or the fail-safe.", stop_hotkey),
+    };
+
+    let (run_stop_label, run_stop_message) = match &scripts.run {
+        Some(_) => ("Stop", Some(Message::StopScript)),
+        None => ("Run", scripts.selected.is_some().then_some(Message::RunScript)),
+    };
+    let run_stop_btn = button(text(run_stop_label).style(if run_stop_message.is_some() {
+        TextStyle::Accent
+    } else {
+        TextStyle::Disabled
+    }))
+    .style(theme::ButtonStyle::Primary)
+    .padding([6, 14]);
+    let run_stop_btn = match run_stop_message {
+        Some(message) => run_stop_btn.on_press(message),
+        None => run_stop_btn,
+    };
+
+    let picker = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(pick_list(scripts.names.clone(), scripts.selected.clone(), Message::ScriptSelected)
+            .placeholder("Script")
+            .padding(6)
+            .width(Length::Fill))
+        .push(button(text("Refresh").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 14])
+            .on_press(Message::RefreshScripts))
+        .push(run_stop_btn);
+
+    container(
+        column![
+            text("Scripts").size(18),
+            picker,
+            text(state).size(14).style(TextStyle::Disabled),
+        ]
+        .spacing(10),
+    )
+    .style(theme::ContainerStyle::Card)
+    .padding(15)
+    .into()
+}
+
+/// Newest sessions first, with export buttons for the whole history.
+fn history_card(recent_sessions: &[String]) -> Element<'static, Message, AppTheme> {
+    let header = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("History").size(18))
+        .push(Space::with_width(Length::Fill))
+        .push(button(text("Export CSV").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 14])
+            .on_press(Message::ExportHistory(ExportFormat::Csv)))
+        .push(button(text("Export JSON").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 14])
+            .on_press(Message::ExportHistory(ExportFormat::Json)));
+
+    let sessions: Element<_, _, _> = if recent_sessions.is_empty() {
+        text("No sessions yet.").size(14).style(TextStyle::Disabled).into()
+    } else {
+        recent_sessions
+            .iter()
+            .fold(Column::new().spacing(4), |list, session| list.push(text(session).size(13)))
+            .into()
+    };
+
+    container(column![header, sessions].spacing(10))
+        .style(theme::ContainerStyle::Card)
+        .padding(15)
+        .into()
+}
+
+/// Timing benchmark report and the calibration offset it suggests.
+fn timing_card(benchmark: &BenchmarkPanel, offset_us: u64) -> Element<'static, Message, AppTheme> {
+    let running = benchmark.run.is_some();
+    let run_btn = button(text(if running { "Measuring..." } else { "Run Benchmark" }).style(if running {
+        TextStyle::Disabled
+    } else {
+        TextStyle::Accent
+    }))
+    .style(theme::ButtonStyle::Primary)
+    .padding([6, 14]);
+    let run_btn = if running { run_btn } else { run_btn.on_press(Message::RunBenchmark) };
+
+    let reset_btn = button(text("Reset").style(if offset_us > 0 { TextStyle::Accent } else { TextStyle::Disabled }))
+        .style(theme::ButtonStyle::Primary)
+        .padding([6, 14]);
+    let reset_btn = if offset_us > 0 { reset_btn.on_press(Message::ResetCalibration) } else { reset_btn };
+
+    let controls = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text(format!("Offset: {} us", offset_us)).size(14))
+        .push(Space::with_width(Length::Fill))
+        .push(reset_btn)
+        .push(run_btn);
+
+    let mut card = column![text("Timing").size(18), controls].spacing(10);
+    if let Some(report) = &benchmark.report {
+        let suggested = report.suggested_offset_us();
+        card = card.push(container(text(report.to_string()).size(12))
+            .style(theme::ContainerStyle::ReadOnly)
+            .padding(6)
+            .width(Length::Fill));
+        if suggested != offset_us {
+            card = card.push(button(text(format!("Apply offset of {} us", suggested)).style(TextStyle::Accent))
+                .style(theme::ButtonStyle::Primary)
+                .padding([6, 14])
+                .on_press(Message::ApplyCalibration));
+        }
+    }
+
+    container(card)
+        .style(theme::ContainerStyle::Card)
+        .padding(15)
+        .into()
+}
+
+/// Export of chosen clickers and macros to a bundle file, and import of a
+/// bundle after a preview.
+fn bundle_card(bundle: &BundlePanel, clickers: &[Clicker]) -> Element<'static, Message, AppTheme> {
+    let header = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text("Share").size(18))
+        .push(Space::with_width(Length::Fill))
+        .push(button(text("Refresh").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 14])
+            .on_press(Message::RefreshBundles));
+
+    let clicker_choices = clickers.iter().enumerate().fold(Column::new().spacing(6), |choices, (index, clicker)| {
+        choices.push(checkbox(format!("Clicker {}", clicker.config.name), bundle.clickers.contains(&index))
+            .on_toggle(move |selected| Message::BundleClickerToggled(index, selected)))
+    });
+    let macro_choices = bundle.macro_names.iter().fold(clicker_choices, |choices, name| {
+        let toggled = name.clone();
+        choices.push(checkbox(format!("Macro {}", name), bundle.macros.contains(name))
+            .on_toggle(move |selected| Message::BundleMacroToggled(toggled.clone(), selected)))
+    });
+
+    let export_row = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(checkbox("Hotkeys", bundle.hotkeys).on_toggle(Message::BundleHotkeysToggled))
+        .push(text_input("Bundle name", &bundle.name)
+            .on_input(Message::BundleNameChanged)
+            .padding(6)
+            .width(Length::Fill))
+        .push(button(text("Export").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 14])
+            .on_press(Message::ExportBundle));
+
+    let preview_btn = button(text("Preview").style(if bundle.selected.is_some() {
+        TextStyle::Accent
+    } else {
+        TextStyle::Disabled
+    }))
+    .style(theme::ButtonStyle::Primary)
+    .padding([6, 14]);
+    let preview_btn = if bundle.selected.is_some() { preview_btn.on_press(Message::PreviewBundle) } else { preview_btn };
+    let import_row = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(pick_list(bundle.bundle_names.clone(), bundle.selected.clone(), Message::BundleSelected)
+            .placeholder("Bundle")
+            .padding(6)
+            .width(Length::Fill))
+        .push(preview_btn);
+
+    let mut card = column![header, macro_choices, export_row, import_row].spacing(10);
+    if let Some((_, plan)) = &bundle.preview {
+        card = card
+            .push(container(text(plan.to_string()).size(12))
+                .style(theme::ContainerStyle::ReadOnly)
+                .padding(6)
+                .width(Length::Fill))
+            .push(Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(checkbox("Replace same names", bundle.replace).on_toggle(Message::BundleReplaceToggled))
+                .push(Space::with_width(Length::Fill))
+                .push(button(text("Cancel").style(TextStyle::Accent))
+                    .style(theme::ButtonStyle::Primary)
+                    .padding([6, 14])
+                    .on_press(Message::CancelImport))
+                .push(button(text("Import").style(TextStyle::Accent))
+                    .style(theme::ButtonStyle::Primary)
+                    .padding([6, 14])
+                    .on_press(Message::ImportBundle)));
+    }
+
+    container(card)
+        .style(theme::ContainerStyle::Card)
+        .padding(15)
+        .into()
+}
+
+/// Log level, a button to open the log file, and an optional view of its tail.
+fn log_card(level: LogLevel, lines: Option<&[String]>) -> Element<'static, Message, AppTheme> {
+    let controls = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(pick_list(LogLevel::ALL, Some(level), Message::LogLevelSelected)
+            .padding(6)
+            .width(Length::Fill))
+        .push(button(text("Open log").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 14])
+            .on_press(Message::OpenLog))
+        .push(button(text(if lines.is_some() { "Hide" } else { "Recent" }).style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([6, 14])
+            .on_press(Message::ToggleLogViewer));
+
+    let mut card = column![text("Log").size(18), controls].spacing(10);
+    if let Some(lines) = lines {
+        let content = if lines.is_empty() {
+            String::from("The log is empty.")
+        } else {
+            lines.join("\n")
+        };
+        card = card
+            .push(container(scrollable(text(content).size(12)).height(Length::Fixed(160.0)))
+                .style(theme::ContainerStyle::ReadOnly)
+                .padding(6)
+                .width(Length::Fill))
+            .push(button(text("Refresh").style(TextStyle::Accent))
+                .style(theme::ButtonStyle::Primary)
+                .padding([6, 14])
+                .on_press(Message::RefreshLog));
+    }
+
+    container(card)
+        .style(theme::ContainerStyle::Card)
+        .padding(15)
+        .into()
+}
+
+pub fn action_label(action: &Action) -> String {
+    match action {
+        Action::Press(key) => format!("Press {}", key),
+        Action::Scroll(_, notches) if *notches > 1 => format!("{} x{}", action.kind(), notches),
+        _ => action.kind().to_string(),
+    }
+}
+
+/// Asks once before allowing an interval below `warn_below_ms`.
+pub fn low_interval_dialog(interval_ms: u64, limits: &RateLimitSettings) -> Element<'static, Message, AppTheme> {
+    let cap = if limits.max_cps > 0 {
+        format!(
+            "The engine still never sends more than {} clicks per second, all clickers and scripts together (rate_limit.max_cps in settings.json).",
+            limits.max_cps
+        )
+    } else {
+        String::from("The click rate cap is off, so nothing limits how fast this clicks.")
+    };
+
+    let buttons = Row::new()
+        .spacing(10)
+        .push(button(text("Cancel").style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([10, 20])
+            .on_press(Message::CancelLowInterval))
+        .push(button(text(format!("Use {} ms", interval_ms)).style(TextStyle::Accent))
+            .style(theme::ButtonStyle::Primary)
+            .padding([10, 20])
+            .on_press(Message::ConfirmLowInterval));
+
+    let dialog = container(
+        column![
+            text("Very short interval").size(20),
+            text(format!(
+                "{} ms is below {} ms. Clicking this fast can freeze other programs or get input rejected.",
+                interval_ms, limits.warn_below_ms
+            )),
+            text(cap).size(14).style(TextStyle::Disabled),
+            text("You will only be asked once.").size(14).style(TextStyle::Disabled),
+            buttons,
+        ]
+        .spacing(15),
+    )
+    .style(theme::ContainerStyle::Card)
+    .padding(20);
+
+    container(dialog)
+        .padding(20)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+}
+
+/// Compact strip for mini mode: status, interval and click count.
+/// Dragging anywhere on it moves the (borderless) window.
+pub fn mini_view(
+    status: &str,
+    interval_input: &str,
+    click_count: u64,
+    is_running: bool,
+) -> Element<'static, Message, AppTheme> {
+    let strip = Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(text(status.to_string()).size(16))
+        .push(text(format!("{} ms", interval_input)).size(16))
+        .push(text(format!("{} clicks", click_count)).size(16))
+        .push(Space::with_width(Length::Fill))
+        .push(
+            button(text("Full").style(TextStyle::Accent).size(14))
+                .style(theme::ButtonStyle::Primary)
+                .padding([4, 10])
+                .on_press(Message::ToggleMiniMode),
+        );
+
+    mouse_area(
+        container(strip)
+            .padding([6, 10])
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_y()
+            .style(if is_running {
+                theme::ContainerStyle::StatusDisplayRunning
+            } else {
+                theme::ContainerStyle::StatusDisplay
+            }),
+    )
+    .on_press(Message::DragWindow)
+    .into()
+}